@@ -0,0 +1,129 @@
+//! Compares the cost of tracking unpark events in a task handle with the
+//! previous implementation, which stored them in a `Vec` cloned on every
+//! nested `task::with_unpark_event` and every `task::park`.
+//!
+//! The representations are compared in isolation, the previous one using a
+//! copy kept here and the current one through a hidden module of the `task`
+//! module, and the real `task::with_unpark_event` and `task::park` functions
+//! are measured end-to-end. Along with the time taken, the number of heap
+//! allocations per iteration is reported.
+//!
+//! Run with `cargo bench --bench unpark_events`.
+
+extern crate futures;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use futures::Async;
+use futures::task::{self, EventSet, UnparkEvent, Unpark, Task};
+use futures::task::__bench::Events;
+
+const ITERS: usize = 200_000;
+
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+struct Noop;
+
+impl EventSet for Noop {
+    fn insert(&self, _id: usize) {}
+}
+
+impl Unpark for Noop {
+    fn unpark(&self) {}
+}
+
+// The previous representation of a task's unpark events.
+#[derive(Clone)]
+struct VecEvents {
+    set: Vec<UnparkEvent>,
+}
+
+impl VecEvents {
+    fn with_event(&self, event: UnparkEvent) -> VecEvents {
+        let mut set = self.set.clone();
+        set.push(event);
+        VecEvents { set: set }
+    }
+}
+
+fn old_park(set: &Arc<Noop>, events: &VecEvents, depth: usize) -> VecEvents {
+    if depth == 0 {
+        return events.clone()
+    }
+    let events = events.with_event(UnparkEvent::new(set.clone(), depth));
+    old_park(set, &events, depth - 1)
+}
+
+fn new_park(set: &Arc<Noop>, events: &Events, depth: usize) -> Events {
+    if depth == 0 {
+        return events.clone()
+    }
+    let events = events.with_event(UnparkEvent::new(set.clone(), depth));
+    new_park(set, &events, depth - 1)
+}
+
+fn task_park(set: &Arc<Noop>, depth: usize) -> Task {
+    if depth == 0 {
+        return task::park()
+    }
+    let event = UnparkEvent::new(set.clone(), depth);
+    task::with_unpark_event(event, || task_park(set, depth - 1))
+}
+
+fn measure<F: FnMut()>(name: &str, depth: usize, mut f: F) {
+    let allocs = ALLOCS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    let allocs = ALLOCS.load(Ordering::Relaxed) - allocs;
+    let ns = elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64;
+    println!("{:<5} depth {}: {:>6} ns/iter {:>3} allocs/iter",
+             name, depth, ns / ITERS as u64, allocs / ITERS);
+}
+
+fn main() {
+    let set = Arc::new(Noop);
+    for depth in 0..5 {
+        measure("old", depth, || {
+            black_box(old_park(&set, &VecEvents { set: Vec::new() }, depth));
+        });
+        measure("new", depth, || {
+            black_box(new_park(&set, &Events::default(), depth));
+        });
+
+        let set = set.clone();
+        let mut t = task::spawn(futures::lazy(move || {
+            measure("task", depth, || {
+                black_box(task_park(&set, depth));
+            });
+            Ok::<(), ()>(())
+        }));
+        match t.poll_future(Arc::new(Noop)) {
+            Ok(Async::Ready(())) => {}
+            _ => panic!(),
+        }
+        println!();
+    }
+}
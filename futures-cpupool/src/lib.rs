@@ -49,7 +49,7 @@ use std::thread;
 
 use crossbeam::sync::MsQueue;
 use futures::{IntoFuture, Future, oneshot, Oneshot, Complete, Poll, Async};
-use futures::task::{self, Run, Executor};
+use futures::task::{self, Run, Executor, GroupHandle};
 
 /// A thread pool intended to run CPU intensive work.
 ///
@@ -184,6 +184,30 @@ impl CpuPool {
     {
         self.spawn(futures::lazy(f))
     }
+
+    /// Spawns a future onto this thread pool as a child of a group of tasks.
+    ///
+    /// The future `f` is added to the group associated with `group` and
+    /// executed on this thread pool. Unlike `spawn` no proxy future is
+    /// returned; instead the result of `f` is reported to the group's
+    /// `TaskGroup` future. If the group is canceled (for example because a
+    /// sibling failed or the `TaskGroup` was dropped), `f` will be dropped the
+    /// next time it would otherwise be polled.
+    ///
+    /// Panics in `f` are caught so they do not tear down the worker thread,
+    /// and are instead propagated through the group's `TaskGroup` future.
+    pub fn spawn_in<F>(&self, group: &GroupHandle<F::Error>, f: F)
+        where F: Future<Item = ()> + Send + 'static,
+              F::Error: Send + 'static,
+    {
+        // See `spawn` above for why AssertUnwindSafe is ok here. The group
+        // child notices that it's dropped while panicking and records the
+        // panic in the group.
+        let child = AssertUnwindSafe(group.wrap(f)).catch_unwind().then(|_| {
+            Ok(())
+        });
+        task::spawn(child).execute(self.inner.clone());
+    }
 }
 
 fn work(inner: &Inner) {
@@ -2,6 +2,7 @@ extern crate futures;
 extern crate futures_cpupool;
 
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::sync::mpsc::channel;
 use std::thread;
 use std::time::Duration;
 
@@ -63,3 +64,34 @@ fn threads_go_away() {
     }
     panic!("thread didn't exit");
 }
+
+#[test]
+fn spawn_in_group() {
+    let pool = CpuPool::new(2);
+    let (handle, group) = futures::task::group::<u32>();
+    let (tx, rx) = channel::<()>();
+
+    pool.spawn_in(&handle, futures::empty().then(move |r: Result<(), u32>| {
+        drop(tx);
+        r
+    }));
+    pool.spawn_in(&handle, futures::failed(5));
+    drop(handle);
+
+    assert_eq!(group.wait(), Err(5));
+    assert!(rx.recv().is_err());
+}
+
+#[test]
+fn spawn_in_group_panic() {
+    let pool = CpuPool::new(1);
+    let (handle, group) = futures::task::group::<u32>();
+    pool.spawn_in(&handle, futures::lazy(|| -> Result<(), u32> {
+        panic!("child")
+    }));
+    drop(handle);
+    assert!(thread::spawn(|| group.wait()).join().is_err());
+
+    // The worker thread survived the panic.
+    assert_eq!(pool.spawn(done(1)).wait(), Ok(1));
+}
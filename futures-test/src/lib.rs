@@ -0,0 +1,456 @@
+//! Utilities for testing implementations of futures and streams.
+//!
+//! This crate collects the helpers which the `futures` crate uses in its own
+//! test suite so that downstream crates don't have to copy them. It provides:
+//!
+//! * Assertions about the state of a future or stream after a single poll, such
+//!   as `assert_empty` and `sassert_next`.
+//! * `Unpark` implementations to poll with: `unpark_noop`, `unpark_panic` and
+//!   `CountUnpark`, which counts the notifications it receives.
+//! * `poll_once` and `poll_stream_once` to poll an object a single time outside
+//!   of any executor.
+//! * `mock_stream`, a stream which yields a scripted sequence of items, errors
+//!   and `NotReady` gaps.
+//! * `track_drop`, a wrapper which records when a future or stream has been
+//!   dropped.
+//! * `interleave_pending` and `assert_interleaved`, which inject spurious
+//!   `NotReady` results to check that combinators don't assume their inner
+//!   futures are ready on the first poll.
+//!
+//! ```rust
+//! extern crate futures;
+//! extern crate futures_test;
+//!
+//! use futures::{Async, oneshot};
+//! use futures::stream::{self, Stream};
+//! use futures_test::*;
+//!
+//! # fn main() {
+//! let (c, mut p) = oneshot::<u32>();
+//! assert!(poll_once(&mut p).unwrap().is_not_ready());
+//! c.complete(3);
+//! assert_eq!(poll_once(&mut p), Ok(Async::Ready(3)));
+//!
+//! let mut s = stream::iter(vec![Ok::<u32, ()>(1)]).map(|a| a + 1);
+//! sassert_next(&mut s, 2);
+//! sassert_done(&mut s);
+//! # }
+//! ```
+
+#![deny(missing_docs)]
+
+extern crate futures;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use futures::{Future, Poll, Async};
+use futures::stream::Stream;
+use futures::task::{self, SimRng, Unpark};
+
+/// Asserts that the future created by `f` resolves to `result`, blocking the
+/// current thread until it does.
+pub fn assert_done<T, F>(mut f: F, result: Result<T::Item, T::Error>)
+    where T: Future,
+          T::Item: Eq + fmt::Debug,
+          T::Error: Eq + fmt::Debug,
+          F: FnMut() -> T,
+{
+    assert_eq!(f().wait(), result);
+}
+
+/// Asserts that the future created by `f` isn't ready when polled once.
+///
+/// The future is polled with `unpark_panic`, so it must not notify its task
+/// during the poll.
+pub fn assert_empty<T: Future, F: FnMut() -> T>(mut f: F) {
+    assert!(task::spawn(f()).poll_future(unpark_panic()).ok().unwrap().is_not_ready());
+}
+
+/// Asserts that the stream `s` is immediately at its end.
+pub fn sassert_done<S: Stream>(s: &mut S) {
+    match task::spawn(s).poll_stream(unpark_panic()) {
+        Ok(Async::Ready(None)) => {}
+        Ok(Async::Ready(Some(_))) => panic!("stream had more elements"),
+        Ok(Async::NotReady) => panic!("stream wasn't ready"),
+        Err(_) => panic!("stream had an error"),
+    }
+}
+
+/// Asserts that the stream `s` isn't ready when polled once.
+pub fn sassert_empty<S: Stream>(s: &mut S) {
+    match task::spawn(s).poll_stream(unpark_noop()) {
+        Ok(Async::Ready(None)) => panic!("stream is at its end"),
+        Ok(Async::Ready(Some(_))) => panic!("stream had more elements"),
+        Ok(Async::NotReady) => {}
+        Err(_) => panic!("stream had an error"),
+    }
+}
+
+/// Asserts that the stream `s` immediately yields `item`.
+pub fn sassert_next<S: Stream>(s: &mut S, item: S::Item)
+    where S::Item: Eq + fmt::Debug
+{
+    match task::spawn(s).poll_stream(unpark_panic()) {
+        Ok(Async::Ready(None)) => panic!("stream is at its end"),
+        Ok(Async::Ready(Some(e))) => assert_eq!(e, item),
+        Ok(Async::NotReady) => panic!("stream wasn't ready"),
+        Err(_) => panic!("stream had an error"),
+    }
+}
+
+/// Asserts that the stream `s` immediately yields the error `err`.
+pub fn sassert_err<S: Stream>(s: &mut S, err: S::Error)
+    where S::Error: Eq + fmt::Debug
+{
+    match task::spawn(s).poll_stream(unpark_panic()) {
+        Ok(Async::Ready(None)) => panic!("stream is at its end"),
+        Ok(Async::Ready(Some(_))) => panic!("stream had more elements"),
+        Ok(Async::NotReady) => panic!("stream wasn't ready"),
+        Err(e) => assert_eq!(e, err),
+    }
+}
+
+/// Returns an `Unpark` which panics if it's ever notified.
+pub fn unpark_panic() -> Arc<Unpark> {
+    struct Foo;
+
+    impl Unpark for Foo {
+        fn unpark(&self) {
+            panic!("should not be unparked");
+        }
+    }
+
+    Arc::new(Foo)
+}
+
+/// Returns an `Unpark` which ignores all notifications.
+pub fn unpark_noop() -> Arc<Unpark> {
+    struct Foo;
+
+    impl Unpark for Foo {
+        fn unpark(&self) {}
+    }
+
+    Arc::new(Foo)
+}
+
+/// An `Unpark` which counts the notifications it has received.
+///
+/// ```rust
+/// extern crate futures;
+/// extern crate futures_test;
+///
+/// use futures::oneshot;
+/// use futures::task;
+/// use futures_test::CountUnpark;
+///
+/// # fn main() {
+/// let (c, p) = oneshot::<u32>();
+/// let counter = CountUnpark::new();
+/// let mut p = task::spawn(p);
+/// assert!(p.poll_future(counter.clone()).unwrap().is_not_ready());
+/// assert_eq!(counter.count(), 0);
+///
+/// c.complete(1);
+/// assert_eq!(counter.count(), 1);
+/// # }
+/// ```
+pub struct CountUnpark {
+    count: AtomicUsize,
+}
+
+impl CountUnpark {
+    /// Creates a new counter which hasn't been notified yet.
+    pub fn new() -> Arc<CountUnpark> {
+        Arc::new(CountUnpark { count: AtomicUsize::new(0) })
+    }
+
+    /// Returns the number of times `unpark` has been called so far.
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+}
+
+impl Unpark for CountUnpark {
+    fn unpark(&self) {
+        self.count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Polls the future `f` once, outside of any executor.
+///
+/// A new task is created for the poll and any notification sent to it is
+/// ignored. Note that as a new task is created for each call, task-local data
+/// doesn't persist across calls; use `task::spawn` directly for that.
+pub fn poll_once<F: Future>(f: &mut F) -> Poll<F::Item, F::Error> {
+    poll_once_with(f, unpark_noop())
+}
+
+/// Like `poll_once`, but sends notifications for the task to `unpark`.
+pub fn poll_once_with<F: Future>(f: &mut F, unpark: Arc<Unpark>)
+                                 -> Poll<F::Item, F::Error> {
+    task::spawn(f).poll_future(unpark)
+}
+
+/// Polls the stream `s` once, outside of any executor.
+///
+/// See `poll_once` for details.
+pub fn poll_stream_once<S: Stream>(s: &mut S) -> Poll<Option<S::Item>, S::Error> {
+    task::spawn(s).poll_stream(unpark_noop())
+}
+
+/// A step in the script of a `MockStream`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step<T, E> {
+    /// The stream yields the given item.
+    Item(T),
+    /// The stream yields the given error.
+    Error(E),
+    /// The stream returns `NotReady`.
+    NotReady,
+}
+
+/// A stream which yields a scripted sequence of results.
+///
+/// This is created by the `mock_stream` function.
+#[must_use = "streams do nothing unless polled"]
+pub struct MockStream<T, E> {
+    steps: VecDeque<Step<T, E>>,
+}
+
+/// Creates a stream which follows the given script of steps, and ends once all
+/// of them have been consumed.
+///
+/// Each poll of the stream consumes one step. When the step is
+/// `Step::NotReady` the current task is unparked immediately before returning
+/// `NotReady`, so the stream can be driven by any executor, including `wait`.
+///
+/// ```rust
+/// extern crate futures;
+/// extern crate futures_test;
+///
+/// use futures::stream::Stream;
+/// use futures_test::{mock_stream, Step};
+///
+/// # fn main() {
+/// let s = mock_stream(vec![
+///     Step::Item(1),
+///     Step::NotReady,
+///     Step::Error(2),
+///     Step::Item(3),
+/// ]);
+/// let res = s.wait().collect::<Vec<_>>();
+/// assert_eq!(res, vec![Ok(1), Err(2), Ok(3)]);
+/// # }
+/// ```
+pub fn mock_stream<T, E, I>(steps: I) -> MockStream<T, E>
+    where I: IntoIterator<Item=Step<T, E>>,
+{
+    MockStream { steps: steps.into_iter().collect() }
+}
+
+impl<T, E> MockStream<T, E> {
+    /// Returns the number of steps which haven't been consumed yet.
+    pub fn remaining(&self) -> usize {
+        self.steps.len()
+    }
+}
+
+impl<T, E> Stream for MockStream<T, E> {
+    type Item = T;
+    type Error = E;
+
+    fn poll(&mut self) -> Poll<Option<T>, E> {
+        match self.steps.pop_front() {
+            Some(Step::Item(t)) => Ok(Async::Ready(Some(t))),
+            Some(Step::Error(e)) => Err(e),
+            Some(Step::NotReady) => {
+                task::park().unpark();
+                Ok(Async::NotReady)
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+/// A future or stream which records when it has been dropped.
+///
+/// This is created by the `track_drop` function.
+pub struct DropTracked<T> {
+    inner: T,
+    dropped: Arc<AtomicBool>,
+}
+
+/// A handle to check whether the object passed to `track_drop` has been
+/// dropped.
+#[derive(Clone)]
+pub struct DropFlag {
+    dropped: Arc<AtomicBool>,
+}
+
+/// Wraps a future or stream so that it can be observed when it's dropped.
+///
+/// This is useful for testing that combinators drop inner futures, and any
+/// closures they own, as soon as they're no longer needed.
+///
+/// ```rust
+/// extern crate futures;
+/// extern crate futures_test;
+///
+/// use futures::{Future, Async, finished};
+/// use futures_test::{track_drop, poll_once};
+///
+/// # fn main() {
+/// let (a, flag) = track_drop(finished::<u32, u32>(1));
+/// let mut f = a.and_then(|a| finished(a + 1));
+/// assert!(!flag.is_dropped());
+/// assert_eq!(poll_once(&mut f), Ok(Async::Ready(2)));
+/// assert!(flag.is_dropped());
+/// # }
+/// ```
+pub fn track_drop<T>(t: T) -> (DropTracked<T>, DropFlag) {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let tracked = DropTracked {
+        inner: t,
+        dropped: dropped.clone(),
+    };
+    (tracked, DropFlag { dropped: dropped })
+}
+
+impl<T> Drop for DropTracked<T> {
+    fn drop(&mut self) {
+        self.dropped.store(true, Ordering::SeqCst);
+    }
+}
+
+impl<F: Future> Future for DropTracked<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<F::Item, F::Error> {
+        self.inner.poll()
+    }
+}
+
+impl<S: Stream> Stream for DropTracked<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        self.inner.poll()
+    }
+}
+
+impl DropFlag {
+    /// Returns whether the tracked object has been dropped.
+    pub fn is_dropped(&self) -> bool {
+        self.dropped.load(Ordering::SeqCst)
+    }
+}
+
+/// Runs the future built by `f` to completion for a number of different seeds,
+/// asserting that the result is always the same.
+///
+/// The closure is given the seed to use for any `interleave_pending` wrappers
+/// it applies to the components of the future it builds, and the returned
+/// future is itself wrapped with `interleave_pending` as well.
+pub fn assert_interleaved<T, F>(mut f: F)
+    where T: Future,
+          T::Item: Eq + fmt::Debug,
+          T::Error: Eq + fmt::Debug,
+          F: FnMut(u64) -> T,
+{
+    let expected = f(0).wait();
+    for seed in 1..64 {
+        let result = interleave_pending(f(seed), seed).wait();
+        assert_eq!(result, expected, "different result with seed {}", seed);
+    }
+}
+
+/// Wraps a future such that it randomly returns `NotReady`, immediately
+/// unparking the current task when it does so.
+///
+/// This is useful for testing that combinators handle their inner futures not
+/// being ready on the first poll. The same `seed` always produces the same
+/// sequence of spurious `NotReady` results, drawn from a `SimRng` like the
+/// scheduling decisions of a `Simulation`.
+pub fn interleave_pending<F: Future>(f: F, seed: u64) -> InterleavePending<F> {
+    InterleavePending {
+        inner: f,
+        rng: SimRng::new(seed),
+    }
+}
+
+/// Like `interleave_pending`, but for streams.
+pub fn interleave_pending_stream<S: Stream>(s: S, seed: u64)
+                                            -> InterleavePending<S> {
+    InterleavePending {
+        inner: s,
+        rng: SimRng::new(seed),
+    }
+}
+
+/// A future or stream which randomly returns `NotReady`.
+///
+/// This is created by the `interleave_pending` and
+/// `interleave_pending_stream` functions.
+pub struct InterleavePending<T> {
+    inner: T,
+    rng: SimRng,
+}
+
+impl<T> InterleavePending<T> {
+    fn skip(&mut self) -> bool {
+        if self.rng.next_u64() % 2 == 0 {
+            task::park().unpark();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<F: Future> Future for InterleavePending<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<F::Item, F::Error> {
+        if self.skip() {
+            return Ok(Async::NotReady)
+        }
+        self.inner.poll()
+    }
+}
+
+impl<S: Stream> Stream for InterleavePending<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        if self.skip() {
+            return Ok(Async::NotReady)
+        }
+        self.inner.poll()
+    }
+}
+
+/// An extension trait to run a future to completion on a new thread, ignoring
+/// its result.
+pub trait ForgetExt {
+    /// Spawns a new thread which blocks on the completion of this future.
+    fn forget(self);
+}
+
+impl<F> ForgetExt for F
+    where F: Future + Sized + Send + 'static,
+          F::Item: Send,
+          F::Error: Send
+{
+    fn forget(self) {
+        thread::spawn(|| self.wait());
+    }
+}
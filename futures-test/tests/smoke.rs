@@ -0,0 +1,98 @@
+extern crate futures;
+extern crate futures_test;
+
+use futures::{Future, Async, oneshot, empty, finished, failed};
+use futures::stream::{self, Stream};
+use futures::task;
+use futures_test::*;
+
+#[test]
+fn assertions() {
+    assert_done(|| finished::<u32, u32>(1), Ok(1));
+    assert_done(|| failed::<u32, u32>(2), Err(2));
+    assert_empty(empty::<u32, u32>);
+
+    let mut s = stream::iter(vec![Ok(1), Err(2)]);
+    sassert_next(&mut s, 1);
+    sassert_err(&mut s, 2);
+    sassert_done(&mut s);
+
+    let (_tx, mut rx) = stream::channel::<u32, u32>();
+    sassert_empty(&mut rx);
+}
+
+#[test]
+fn count_unpark() {
+    let (tx, rx) = stream::channel::<u32, u32>();
+    let counter = CountUnpark::new();
+    let mut rx = task::spawn(rx);
+    assert!(rx.poll_stream(counter.clone()).unwrap().is_not_ready());
+    assert_eq!(counter.count(), 0);
+
+    let tx = tx.send(Ok(1)).wait().ok().unwrap();
+    assert_eq!(counter.count(), 1);
+    assert_eq!(rx.poll_stream(counter.clone()), Ok(Async::Ready(Some(1))));
+    drop(tx);
+    assert_eq!(counter.count(), 1);
+}
+
+#[test]
+fn poll_outside_executor() {
+    let (c, mut p) = oneshot::<u32>();
+    assert!(poll_once(&mut p).unwrap().is_not_ready());
+    c.complete(2);
+    assert_eq!(poll_once_with(&mut p, unpark_panic()), Ok(Async::Ready(2)));
+
+    let mut s = stream::iter(vec![Ok::<u32, u32>(1)]);
+    assert_eq!(poll_stream_once(&mut s), Ok(Async::Ready(Some(1))));
+    assert_eq!(poll_stream_once(&mut s), Ok(Async::Ready(None)));
+}
+
+#[test]
+fn mock_stream_gaps() {
+    let mut s = mock_stream(vec![
+        Step::NotReady,
+        Step::Item(1),
+        Step::Error(2),
+        Step::NotReady,
+        Step::NotReady,
+        Step::Item(3),
+    ]);
+    let counter = CountUnpark::new();
+    {
+        let mut s = task::spawn(&mut s);
+        assert!(s.poll_stream(counter.clone()).unwrap().is_not_ready());
+        assert_eq!(counter.count(), 1);
+        assert_eq!(s.poll_stream(counter.clone()), Ok(Async::Ready(Some(1))));
+        assert_eq!(s.poll_stream(counter.clone()), Err(2));
+    }
+    assert_eq!(s.remaining(), 3);
+    assert_eq!(s.collect().wait(), Ok(vec![3]));
+}
+
+#[test]
+fn drop_tracking() {
+    let (c, p) = oneshot::<u32>();
+    let (p, flag) = track_drop(p);
+    let (tx, rx) = ::std::sync::mpsc::channel();
+    p.then(move |r| tx.send(r.ok()).map_err(|_| ())).forget();
+    assert!(!flag.is_dropped());
+    c.complete(1);
+    assert_eq!(rx.recv().unwrap(), Some(1));
+    assert!(flag.is_dropped());
+
+    let (s, flag) = track_drop(stream::iter(vec![Ok::<u32, u32>(1), Ok(2)]));
+    let mut s = s.take(1);
+    sassert_next(&mut s, 1);
+    assert!(!flag.is_dropped());
+    drop(s);
+    assert!(flag.is_dropped());
+}
+
+#[test]
+fn interleaved() {
+    assert_interleaved(|seed| {
+        interleave_pending_stream(stream::iter((0..5).map(Ok::<u32, u32>)), seed)
+            .fold(0, |a, b| Ok::<u32, u32>(a + b))
+    });
+}
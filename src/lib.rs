@@ -205,19 +205,26 @@ if_std! {
     mod lock;
     mod slot;
     pub mod task;
+    pub mod sync;
 
     mod catch_unwind;
     mod collect;
     mod oneshot;
     mod select_all;
+    mod supervise;
     pub use catch_unwind::CatchUnwind;
     pub use collect::{collect, Collect};
     pub use oneshot::{oneshot, Oneshot, Complete, Canceled};
     pub use select_all::{SelectAll, SelectAllNext, select_all};
+    pub use supervise::{supervise, Supervisor, SupervisorError, SupervisorEvent};
+    pub use supervise::{SupervisorEvents, RestartPolicy};
 
     /// A type alias for `Box<Future + Send>`
     pub type BoxFuture<T, E> = std::boxed::Box<Future<Item = T, Error = E> + Send>;
 
+    /// A type alias for `Box<Future>`, without the `Send` bound of `BoxFuture`
+    pub type LocalBoxFuture<T, E> = std::boxed::Box<Future<Item = T, Error = E>>;
+
     impl<F: ?Sized + Future> Future for std::boxed::Box<F> {
         type Item = F::Item;
         type Error = F::Error;
@@ -389,7 +396,7 @@ pub trait Future {
     /// type inference as well by always returning a trait object. Note that
     /// this method requires the `Send` bound and returns a `BoxFuture`, which
     /// also encodes this. If you'd like to create a `Box<Future>` without the
-    /// `Send` bound, then the `Box::new` function can be used instead.
+    /// `Send` bound, then the `boxed_local` method can be used instead.
     ///
     /// # Examples
     ///
@@ -405,6 +412,28 @@ pub trait Future {
         ::std::boxed::Box::new(self)
     }
 
+    /// Convenience function for turning this future into a trait object which
+    /// isn't required to be `Send`.
+    ///
+    /// This is the same as `boxed`, except that it returns a `LocalBoxFuture`
+    /// and so can be used for futures which can't be sent to other threads,
+    /// for example because they hold an `Rc`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use futures::*;
+    ///
+    /// let a: LocalBoxFuture<Rc<i32>, i32> = done(Ok(Rc::new(1))).boxed_local();
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn boxed_local(self) -> LocalBoxFuture<Self::Item, Self::Error>
+        where Self: Sized + 'static
+    {
+        ::std::boxed::Box::new(self)
+    }
+
     /// Map this future's result to a different type, returning a new future of
     /// the resulting type.
     ///
@@ -2,9 +2,8 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use {Future, Poll, Async};
-use slot::{Slot, Token};
-use lock::Lock;
-use task::{self, Task};
+use slot::Slot;
+use task::AtomicTask;
 
 /// A future representing the completion of a computation happening elsewhere in
 /// memory.
@@ -13,7 +12,6 @@ use task::{self, Task};
 #[must_use = "futures do nothing unless polled"]
 pub struct Oneshot<T> {
     inner: Arc<Inner<T>>,
-    cancel_token: Option<Token>,
 }
 
 /// Represents the completion half of a oneshot through which the result of a
@@ -28,7 +26,7 @@ pub struct Complete<T> {
 struct Inner<T> {
     slot: Slot<Option<T>>,
     oneshot_gone: AtomicBool,
-    notify_cancel: Lock<Option<Task>>,
+    notify_cancel: AtomicTask,
 }
 
 /// Creates a new in-memory oneshot used to represent completing a computation.
@@ -65,11 +63,10 @@ pub fn oneshot<T>() -> (Complete<T>, Oneshot<T>) {
     let inner = Arc::new(Inner {
         slot: Slot::new(None),
         oneshot_gone: AtomicBool::new(false),
-        notify_cancel: Lock::new(None),
+        notify_cancel: AtomicTask::new(),
     });
     let oneshot = Oneshot {
         inner: inner.clone(),
-        cancel_token: None,
     };
     let complete = Complete {
         inner: inner,
@@ -84,9 +81,49 @@ impl<T> Complete<T> {
     /// This function will consume `self` and indicate to the other end, the
     /// `Oneshot`, that the error provided is the result of the computation this
     /// represents.
-    pub fn complete(mut self, t: T) {
+    ///
+    /// If the `Oneshot` has gone away the value is dropped, see `send` for a
+    /// way to get it back instead.
+    pub fn complete(self, t: T) {
+        drop(self.send(t));
+    }
+
+    /// Completes this oneshot with a successful result, returning the value
+    /// back if it can't be received.
+    ///
+    /// This is like `complete`, except that if the `Oneshot` has already been
+    /// dropped or closed then `Err` is returned with the value which would
+    /// have been sent, and the `Oneshot` (if it's still around) resolves to
+    /// `Canceled` instead.
+    ///
+    /// Note that the `Oneshot` may still go away concurrently with this
+    /// method, in which case `Ok` is returned but the value is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::oneshot;
+    ///
+    /// let (c, p) = oneshot::<i32>();
+    /// drop(p);
+    /// assert_eq!(c.send(3), Err(3));
+    /// ```
+    pub fn send(mut self, t: T) -> Result<(), T> {
+        if self.is_canceled() {
+            return Err(t)
+        }
         self.completed = true;
-        self.send(Some(t))
+        self.fill(Some(t));
+        Ok(())
+    }
+
+    /// Returns whether the `Oneshot` paired with this `Complete` has been
+    /// dropped or closed, so any value sent would not be received.
+    ///
+    /// Unlike `poll_cancel`, this doesn't require being called from within a
+    /// task and doesn't arrange for the current task to be notified.
+    pub fn is_canceled(&self) -> bool {
+        self.inner.oneshot_gone.load(Ordering::SeqCst)
     }
 
     /// Polls this `Complete` half to detect whether the `Oneshot` this has
@@ -101,8 +138,8 @@ impl<T> Complete<T> {
     /// called from inside another future.
     ///
     /// If `Ready` is returned then it means that the `Oneshot` has disappeared
-    /// and the result this `Complete` would otherwise produce should no longer
-    /// be produced.
+    /// or been closed, and the result this `Complete` would otherwise produce
+    /// should no longer be produced.
     ///
     /// If `NotReady` is returned then the `Oneshot` is still alive and may be
     /// able to receive a message if sent. The current task, however, is
@@ -115,24 +152,13 @@ impl<T> Complete<T> {
             return Ok(Async::Ready(()))
         }
 
-        // If our other half is not gone then we need to park our current task
-        // and move it into the `notify_cancel` slot to get notified when it's
-        // actually gone.
-        //
-        // If `try_lock` fails, then the `Oneshot` is in the process of using
-        // it, so we can deduce that it's now in the process of going away and
-        // hence we're canceled. If it succeeds then we just store our handle.
+        // If our other half is not gone then we need to register our current
+        // task in `notify_cancel` to get notified when it's actually gone.
         //
         // Crucially we then check `oneshot_gone` *again* before we return.
-        // While we were storing our handle inside `notify_cancel` the `Oneshot`
-        // may have been dropped. The first thing it does is set the flag, and
-        // if it fails to acquire the lock it assumes that we'll see the flag
-        // later on. So... we then try to see the flag later on!
-        let handle = task::park();
-        match self.inner.notify_cancel.try_lock() {
-            Some(mut p) => *p = Some(handle),
-            None => return Ok(Async::Ready(())),
-        }
+        // While we were registering our task the `Oneshot` may have been
+        // dropped, having set the flag before there was a task to notify.
+        self.inner.notify_cancel.register();
         if self.inner.oneshot_gone.load(Ordering::SeqCst) {
             Ok(Async::Ready(()))
         } else {
@@ -140,7 +166,7 @@ impl<T> Complete<T> {
         }
     }
 
-    fn send(&mut self, t: Option<T>) {
+    fn fill(&mut self, t: Option<T>) {
         if let Err(e) = self.inner.slot.try_produce(t) {
             self.inner.slot.on_empty(Some(e.into_inner()), |slot, item| {
                 slot.try_produce(item.unwrap()).ok()
@@ -153,13 +179,13 @@ impl<T> Complete<T> {
 impl<T> Drop for Complete<T> {
     fn drop(&mut self) {
         if !self.completed {
-            self.send(None);
+            self.fill(None);
         }
     }
 }
 
 /// Error returned from a `Oneshot<T>` whenever the correponding `Complete<T>`
-/// is dropped.
+/// is dropped without sending a value.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Canceled;
 
@@ -168,46 +194,74 @@ impl<T> Future for Oneshot<T> {
     type Error = Canceled;
 
     fn poll(&mut self) -> Poll<T, Canceled> {
-        if let Some(cancel_token) = self.cancel_token.take() {
-            self.inner.slot.cancel(cancel_token);
-        }
-        match self.inner.slot.try_consume() {
+        match self.inner.slot.poll_consume() {
             Ok(Some(e)) => Ok(Async::Ready(e)),
             Ok(None) => Err(Canceled),
-            Err(_) => {
-                let task = task::park();
-                self.cancel_token = Some(self.inner.slot.on_full(move |_| {
-                    task.unpark();
-                }));
-                Ok(Async::NotReady)
-            }
+            Err(_) => Ok(Async::NotReady),
         }
     }
 }
 
-impl<T> Drop for Oneshot<T> {
-    fn drop(&mut self) {
-        // First up, if we squirreled away a task to get notified once the
-        // oneshot was filled in, we cancel that notification. We'll never end
-        // up actually receiving data (as we're being dropped) so no need to
-        // hold onto the task.
-        if let Some(cancel_token) = self.cancel_token.take() {
-            self.inner.slot.cancel(cancel_token)
+impl<T> Oneshot<T> {
+    /// Attempts to receive the value of this oneshot without blocking or
+    /// parking the current task.
+    ///
+    /// Returns `Ok(Some(t))` if the value has been sent, `Ok(None)` if it
+    /// hasn't been sent yet, and `Err(Canceled)` if the `Complete` half was
+    /// dropped without sending a value. Unlike `poll` this can be called from
+    /// outside of a task, but no notification is arranged when the value
+    /// arrives.
+    ///
+    /// Once a value or `Canceled` has been returned, either from this method
+    /// or from `poll`, this method should not be called again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::oneshot;
+    ///
+    /// let (c, mut p) = oneshot::<i32>();
+    /// assert_eq!(p.try_recv(), Ok(None));
+    /// c.complete(3);
+    /// assert_eq!(p.try_recv(), Ok(Some(3)));
+    /// ```
+    pub fn try_recv(&mut self) -> Result<Option<T>, Canceled> {
+        match self.inner.slot.try_consume() {
+            Ok(Some(e)) => Ok(Some(e)),
+            Ok(None) => Err(Canceled),
+            Err(_) => Ok(None),
         }
+    }
 
-        // Next up, inform the `Complete` half that we're going away. First up
-        // we flag ourselves as gone, and next we'll attempt to wake up any
-        // handle that was stored.
+    /// Signals to the `Complete` half that the value is no longer wanted,
+    /// without dropping this `Oneshot`.
+    ///
+    /// After this method is called `Complete::is_canceled` returns `true`,
+    /// `Complete::poll_cancel` resolves and `Complete::send` returns the value
+    /// back to the sender. A value sent before the oneshot was closed can
+    /// still be received, otherwise this oneshot resolves to `Canceled` once
+    /// the `Complete` is dropped.
+    pub fn close(&mut self) {
+        // First up we flag ourselves as gone, and next we wake up any task
+        // that was registered.
         //
-        // If we fail to acquire the lock on the handle, that means that a
-        // `Complete` is in the process of storing one, and it'll check
-        // `oneshot_gone` on its way out to see our write here.
+        // If a `Complete` is in the process of registering its task, it'll
+        // check `oneshot_gone` on its way out to see our write here.
         self.inner.oneshot_gone.store(true, Ordering::SeqCst);
-        if let Some(mut handle) = self.inner.notify_cancel.try_lock() {
-            if let Some(task) = handle.take() {
-                drop(handle);
-                task.unpark()
-            }
-        }
+        self.inner.notify_cancel.notify();
+    }
+}
+
+impl<T> Drop for Oneshot<T> {
+    fn drop(&mut self) {
+        // First up, if we registered our task to get notified once the
+        // oneshot was filled in, we forget it. We'll never end up actually
+        // receiving data (as we're being dropped) so no need to hold onto the
+        // task.
+        self.inner.slot.unregister_consumer();
+
+        // Next up, inform the `Complete` half that we're going away, which is
+        // exactly what closing does.
+        self.close();
     }
 }
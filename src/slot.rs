@@ -11,6 +11,7 @@ use std::prelude::v1::*;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use lock::Lock;
+use task::AtomicTask;
 
 /// A slot in memory intended to represent the communication channel between one
 /// producer and one consumer.
@@ -42,6 +43,13 @@ use lock::Lock;
 /// started running can be canceled. Canceling a callback that has already run
 /// is not an error, and `cancel` does not signal whether or not the callback
 /// was actually canceled to the caller.
+///
+/// # Waiting from a task
+///
+/// As an alternative to callbacks, [`poll_consume`](#method.poll_consume) and
+/// [`poll_produce`](#method.poll_produce) register the current task to be
+/// unparked when the slot becomes full or empty respectively, if the value
+/// can't be consumed or produced right away.
 pub struct Slot<T> {
     // The purpose of this data type is to communicate when a value becomes
     // available and coordinate between a producer and consumer about that
@@ -63,6 +71,11 @@ pub struct Slot<T> {
     slot: Lock<Option<T>>,
     on_full: Lock<Option<Box<FnBox<T>>>>,
     on_empty: Lock<Option<(Box<FnBox2<T>>, Option<T>)>>,
+
+    // Tasks waiting for the slot to become full and empty respectively. These
+    // are notified on every transition, independently of the callbacks.
+    consumer: AtomicTask,
+    producer: AtomicTask,
 }
 
 /// Error value returned from erroneous calls to `try_produce`, which contains
@@ -119,6 +132,8 @@ impl<T> Slot<T> {
             slot: Lock::new(val),
             on_full: Lock::new(None),
             on_empty: Lock::new(None),
+            consumer: AtomicTask::new(),
+            producer: AtomicTask::new(),
         }
     }
 
@@ -190,9 +205,32 @@ impl<T> Slot<T> {
                                  .take().expect("ON_FULL but no callback");
             cb.call_box(self);
         }
+        self.consumer.notify();
         Ok(())
     }
 
+    /// Attempts to store `t` in the slot, registering the current task to be
+    /// unparked once the slot becomes empty if it's full.
+    ///
+    /// This is the same as `try_produce` except for registering the task, and
+    /// has the same restrictions.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if called outside the context of a task, or in
+    /// the same situations as `try_produce`.
+    pub fn poll_produce(&self, t: T) -> Result<(), TryProduceError<T>> {
+        let t = match self.try_produce(t) {
+            Ok(()) => return Ok(()),
+            Err(e) => e.into_inner(),
+        };
+
+        // The slot may have been emptied before our task was registered, in
+        // which case we won't get a notification, so try once more.
+        self.producer.register();
+        self.try_produce(t)
+    }
+
     /// Registers `f` as a callback to run when the slot becomes empty.
     ///
     /// The callback will run immediately if the slot is already empty. Returns
@@ -312,9 +350,34 @@ impl<T> Slot<T> {
             let (cb, item) = cb;
             cb.call_box(self, item);
         }
+        self.producer.notify();
         Ok(val)
     }
 
+    /// Attempts to consume the value stored in the slot, registering the
+    /// current task to be unparked once the slot becomes full if it's empty.
+    ///
+    /// This is the same as `try_consume` except for registering the task, and
+    /// has the same restrictions.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if called outside the context of a task, or in
+    /// the same situations as `try_consume`.
+    pub fn poll_consume(&self) -> Result<T, TryConsumeError> {
+        if let Ok(val) = self.try_consume() {
+            return Ok(val)
+        }
+        self.consumer.register();
+        self.try_consume()
+    }
+
+    /// Forgets the task registered by `poll_consume`, if any, so it isn't
+    /// unparked when the slot becomes full.
+    pub fn unregister_consumer(&self) {
+        drop(self.consumer.take());
+    }
+
     /// Registers `f` as a callback to run when the slot becomes full.
     ///
     /// The callback will run immediately if the slot is already full. Returns a
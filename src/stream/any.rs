@@ -0,0 +1,43 @@
+use {Async, Future, Poll};
+use stream::Stream;
+
+/// A future which tests whether any item of a stream satisfies a predicate.
+///
+/// This future is created by the `Stream::any` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct Any<S, P> {
+    stream: S,
+    pred: P,
+}
+
+pub fn new<S, P>(s: S, pred: P) -> Any<S, P>
+    where S: Stream,
+          P: FnMut(S::Item) -> bool,
+{
+    Any {
+        stream: s,
+        pred: pred,
+    }
+}
+
+impl<S, P> Future for Any<S, P>
+    where S: Stream,
+          P: FnMut(S::Item) -> bool,
+{
+    type Item = bool;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<bool, S::Error> {
+        loop {
+            match try!(self.stream.poll()) {
+                Async::Ready(Some(e)) => {
+                    if (self.pred)(e) {
+                        return Ok(Async::Ready(true))
+                    }
+                }
+                Async::Ready(None) => return Ok(Async::Ready(false)),
+                Async::NotReady => return Ok(Async::NotReady),
+            }
+        }
+    }
+}
@@ -2,9 +2,8 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use {Future, Poll, Async};
-use slot::{Slot, Token};
+use slot::Slot;
 use stream::Stream;
-use task;
 
 /// Creates an in-memory channel implementation of the `Stream` trait.
 ///
@@ -27,7 +26,6 @@ pub fn channel<T, E>() -> (Sender<T, E>, Receiver<T, E>) {
     };
     let receiver = Receiver {
         inner: inner,
-        on_full_token: None,
     };
     (sender, receiver)
 }
@@ -45,7 +43,6 @@ pub struct Sender<T, E> {
 pub struct FutureSender<T, E> {
     sender: Option<Sender<T, E>>,
     data: Option<Result<T, E>>,
-    on_empty_token: Option<Token>,
 }
 
 /// The receiving end of a channel which implements the `Stream` trait.
@@ -56,7 +53,6 @@ pub struct FutureSender<T, E> {
 #[must_use = "streams do nothing unless polled"]
 pub struct Receiver<T, E> {
     inner: Arc<Inner<T, E>>,
-    on_full_token: Option<Token>,
 }
 
 struct Inner<T, E> {
@@ -76,21 +72,11 @@ impl<T, E> Stream for Receiver<T, E> {
     type Error = E;
 
     fn poll(&mut self) -> Poll<Option<T>, E> {
-        if let Some(token) = self.on_full_token.take() {
-            self.inner.slot.cancel(token);
-        }
-
-        match self.inner.slot.try_consume() {
+        match self.inner.slot.poll_consume() {
             Ok(Message::Data(Ok(e))) => Ok(Async::Ready(Some(e))),
             Ok(Message::Data(Err(e))) => Err(e),
             Ok(Message::Done) => Ok(Async::Ready(None)),
-            Err(..) => {
-                let task = task::park();
-                self.on_full_token = Some(self.inner.slot.on_full(move |_| {
-                    task.unpark();
-                }));
-                Ok(Async::NotReady)
-            }
+            Err(..) => Ok(Async::NotReady),
         }
     }
 }
@@ -98,9 +84,7 @@ impl<T, E> Stream for Receiver<T, E> {
 impl<T, E> Drop for Receiver<T, E> {
     fn drop(&mut self) {
         self.inner.receiver_gone.store(true, Ordering::SeqCst);
-        if let Some(token) = self.on_full_token.take() {
-            self.inner.slot.cancel(token);
-        }
+        self.inner.slot.unregister_consumer();
         self.inner.slot.on_full(|slot| {
             drop(slot.try_consume());
         });
@@ -116,7 +100,6 @@ impl<T, E> Sender<T, E> {
         FutureSender {
             sender: Some(self),
             data: Some(t),
-            on_empty_token: None,
         }
     }
 }
@@ -136,20 +119,12 @@ impl<T, E> Future for FutureSender<T, E> {
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
         let data = self.data.take().expect("cannot poll FutureSender twice");
         let sender = self.sender.take().expect("cannot poll FutureSender twice");
-        if let Some(token) = self.on_empty_token.take() {
-            sender.inner.slot.cancel(token);
-        }
         if sender.inner.receiver_gone.load(Ordering::SeqCst) {
             return Err(SendError(data))
         }
-        match sender.inner.slot.try_produce(Message::Data(data)) {
+        match sender.inner.slot.poll_produce(Message::Data(data)) {
             Ok(()) => Ok(Async::Ready(sender)),
             Err(e) => {
-                let task = task::park();
-                let token = sender.inner.slot.on_empty(None, move |_slot, _item| {
-                    task.unpark();
-                });
-                self.on_empty_token = Some(token);
                 self.data = Some(match e.into_inner() {
                     Message::Data(data) => data,
                     Message::Done => panic!(),
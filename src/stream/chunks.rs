@@ -0,0 +1,80 @@
+use std::prelude::v1::*;
+
+use std::mem;
+
+use {Async, Poll};
+use stream::{Stream, Fuse};
+
+/// An adaptor for a stream which collects its items into batches.
+///
+/// This structure is produced by the `Stream::chunks` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Chunks<S>
+    where S: Stream,
+{
+    stream: Fuse<S>,
+    items: Vec<S::Item>,
+    err: Option<S::Error>,
+    cap: usize,
+}
+
+pub fn new<S>(s: S, capacity: usize) -> Chunks<S>
+    where S: Stream,
+{
+    assert!(capacity > 0, "chunk size must be greater than zero");
+    Chunks {
+        stream: super::fuse::new(s),
+        items: Vec::with_capacity(capacity),
+        err: None,
+        cap: capacity,
+    }
+}
+
+impl<S> Chunks<S>
+    where S: Stream,
+{
+    fn take(&mut self) -> Vec<S::Item> {
+        let cap = self.cap;
+        mem::replace(&mut self.items, Vec::with_capacity(cap))
+    }
+}
+
+impl<S> Stream for Chunks<S>
+    where S: Stream,
+{
+    type Item = Vec<S::Item>;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Some(err) = self.err.take() {
+            return Err(err)
+        }
+        loop {
+            match self.stream.poll() {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(Some(item))) => {
+                    self.items.push(item);
+                    if self.items.len() >= self.cap {
+                        return Ok(Async::Ready(Some(self.take())))
+                    }
+                }
+
+                // Flush the items we've got before signaling the end of the
+                // stream or its error, which is returned on the next poll.
+                Ok(Async::Ready(None)) => {
+                    if self.items.is_empty() {
+                        return Ok(Async::Ready(None))
+                    }
+                    return Ok(Async::Ready(Some(self.take())))
+                }
+                Err(e) => {
+                    if self.items.is_empty() {
+                        return Err(e)
+                    }
+                    self.err = Some(e);
+                    return Ok(Async::Ready(Some(self.take())))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use core::mem;
+
+use {Future, Poll, Async};
+use stream::Stream;
+
+/// A future which collects all of the values of a stream into a collection of
+/// any type implementing `Default` and `Extend`.
+///
+/// This future is created by the `Stream::collect_into` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct CollectInto<S, C> {
+    stream: S,
+    items: C,
+}
+
+pub fn new<S, C>(s: S) -> CollectInto<S, C>
+    where S: Stream,
+          C: Default + Extend<S::Item>,
+{
+    CollectInto {
+        stream: s,
+        items: C::default(),
+    }
+}
+
+impl<S, C> Future for CollectInto<S, C>
+    where S: Stream,
+          C: Default + Extend<S::Item>,
+{
+    type Item = C;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<C, S::Error> {
+        loop {
+            match self.stream.poll() {
+                Ok(Async::Ready(Some(e))) => self.items.extend(Some(e)),
+                Ok(Async::Ready(None)) => {
+                    return Ok(Async::Ready(mem::take(&mut self.items)))
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.items = C::default();
+                    return Err(e)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use {Async, Future, Poll};
+use stream::Stream;
+
+/// A future which concatenates all of the items of a stream into one.
+///
+/// This future is created by the `Stream::concat` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct Concat<S>
+    where S: Stream,
+{
+    stream: S,
+    items: Option<S::Item>,
+}
+
+pub fn new<S>(s: S) -> Concat<S>
+    where S: Stream,
+          S::Item: Extend<<S::Item as IntoIterator>::Item> + IntoIterator +
+                   Default,
+{
+    Concat {
+        stream: s,
+        items: None,
+    }
+}
+
+impl<S> Future for Concat<S>
+    where S: Stream,
+          S::Item: Extend<<S::Item as IntoIterator>::Item> + IntoIterator +
+                   Default,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<S::Item, S::Error> {
+        loop {
+            match self.stream.poll() {
+                Ok(Async::Ready(Some(e))) => {
+                    match self.items {
+                        Some(ref mut items) => items.extend(e),
+                        None => self.items = Some(e),
+                    }
+                }
+                Ok(Async::Ready(None)) => {
+                    let items = self.items.take().unwrap_or_default();
+                    return Ok(Async::Ready(items))
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.items = None;
+                    return Err(e)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use {Async, Future, Poll};
+use stream::Stream;
+
+/// A future which counts the items of a stream.
+///
+/// This future is created by the `Stream::count` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct Count<S> {
+    stream: S,
+    count: usize,
+}
+
+pub fn new<S>(s: S) -> Count<S>
+    where S: Stream,
+{
+    Count {
+        stream: s,
+        count: 0,
+    }
+}
+
+impl<S> Future for Count<S>
+    where S: Stream,
+{
+    type Item = usize;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<usize, S::Error> {
+        loop {
+            match try!(self.stream.poll()) {
+                Async::Ready(Some(_)) => self.count += 1,
+                Async::Ready(None) => return Ok(Async::Ready(self.count)),
+                Async::NotReady => return Ok(Async::NotReady),
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use {Async, Poll};
+use stream::Stream;
+
+/// A stream combinator which pairs each element of a stream with its index.
+///
+/// This is produced by the `Stream::enumerate` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Enumerate<S> {
+    stream: S,
+    count: usize,
+}
+
+pub fn new<S>(s: S) -> Enumerate<S>
+    where S: Stream,
+{
+    Enumerate {
+        stream: s,
+        count: 0,
+    }
+}
+
+impl<S> Stream for Enumerate<S>
+    where S: Stream,
+{
+    type Item = (usize, S::Item);
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, S::Error> {
+        match try_ready!(self.stream.poll()) {
+            Some(item) => {
+                let count = self.count;
+                self.count += 1;
+                Ok(Async::Ready(Some((count, item))))
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
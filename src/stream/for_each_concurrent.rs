@@ -0,0 +1,97 @@
+use std::prelude::v1::*;
+
+use {Async, Future, IntoFuture, Poll};
+use stream::Stream;
+
+/// A future which runs a future for each item of a stream, running up to a
+/// fixed number of them at once.
+///
+/// This future is created by the `Stream::for_each_concurrent` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct ForEachConcurrent<S, F, U>
+    where U: IntoFuture,
+{
+    stream: S,
+    f: F,
+    limit: usize,
+    stream_done: bool,
+    running: Vec<U::Future>,
+}
+
+pub fn new<S, F, U>(s: S, limit: usize, f: F) -> ForEachConcurrent<S, F, U>
+    where S: Stream,
+          F: FnMut(S::Item) -> U,
+          U: IntoFuture<Item = (), Error = S::Error>,
+{
+    assert!(limit > 0, "for_each_concurrent limit must be greater than zero");
+    ForEachConcurrent {
+        stream: s,
+        f: f,
+        limit: limit,
+        stream_done: false,
+        running: Vec::with_capacity(limit),
+    }
+}
+
+impl<S, F, U> Future for ForEachConcurrent<S, F, U>
+    where S: Stream,
+          F: FnMut(S::Item) -> U,
+          U: IntoFuture<Item = (), Error = S::Error>,
+{
+    type Item = ();
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<(), S::Error> {
+        loop {
+            // First, start a future for each item the stream has ready, as
+            // long as there's room for it.
+            while !self.stream_done && self.running.len() < self.limit {
+                match self.stream.poll() {
+                    Ok(Async::Ready(Some(item))) => {
+                        self.running.push((self.f)(item).into_future());
+                    }
+                    Ok(Async::Ready(None)) => self.stream_done = true,
+                    Ok(Async::NotReady) => break,
+                    Err(e) => return Err(self.fail(e)),
+                }
+            }
+
+            // Next, step all the running futures forward, removing those
+            // which are done.
+            let before = self.running.len();
+            let mut i = 0;
+            while i < self.running.len() {
+                match self.running[i].poll() {
+                    Ok(Async::Ready(())) => {
+                        self.running.swap_remove(i);
+                    }
+                    Ok(Async::NotReady) => i += 1,
+                    Err(e) => return Err(self.fail(e)),
+                }
+            }
+
+            if self.stream_done && self.running.is_empty() {
+                return Ok(Async::Ready(()))
+            }
+
+            // If a future finished while the limit was reached, the stream
+            // may have more items ready to fill its place.
+            if self.stream_done ||
+               before < self.limit ||
+               self.running.len() == before {
+                return Ok(Async::NotReady)
+            }
+        }
+    }
+}
+
+impl<S, F, U> ForEachConcurrent<S, F, U>
+    where U: IntoFuture,
+{
+    // Drops all the running futures upon an error, so that no more work is
+    // done on behalf of this future.
+    fn fail<E>(&mut self, e: E) -> E {
+        self.running.clear();
+        e
+    }
+}
@@ -0,0 +1,163 @@
+use std::prelude::v1::*;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use {Async, Poll};
+use stream::Stream;
+use super::waiters::{self, Waiters, Wakeups};
+
+/// One of the streams sharing the items of another stream.
+///
+/// This is created by the `Stream::fork` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Fork<S>
+    where S: Stream,
+{
+    shared: Arc<Shared<S>>,
+    id: usize,
+}
+
+struct Shared<S>
+    where S: Stream,
+{
+    inner: Mutex<Inner<S>>,
+    waiters: Arc<Waiters>,
+}
+
+struct Inner<S>
+    where S: Stream,
+{
+    stream: S,
+    capacity: usize,
+    done: bool,
+    // The items not yet taken by each fork, or `None` once it's dropped.
+    queues: Vec<Option<Queue<S>>>,
+    // The number of queues which are full.
+    full: usize,
+}
+
+type Queue<S> = VecDeque<Result<<S as Stream>::Item, <S as Stream>::Error>>;
+
+pub fn new<S>(s: S, n: usize, capacity: usize) -> Vec<Fork<S>>
+    where S: Stream,
+          S::Item: Clone,
+          S::Error: Clone,
+{
+    assert!(capacity > 0, "fork capacity must be greater than zero");
+    let shared = Arc::new(Shared {
+        inner: Mutex::new(Inner {
+            stream: s,
+            capacity: capacity,
+            done: false,
+            queues: (0..n).map(|_| Some(VecDeque::new())).collect(),
+            full: 0,
+        }),
+        waiters: Arc::new(Waiters::new()),
+    });
+    (0..n).map(|id| {
+        Fork {
+            shared: shared.clone(),
+            id: id,
+        }
+    }).collect()
+}
+
+impl<S> Shared<S>
+    where S: Stream,
+          S::Item: Clone,
+          S::Error: Clone,
+{
+    // Pulls items from the underlying stream into the queues of all the
+    // forks, until it isn't ready or one of the queues is full.
+    fn pull(&self, inner: &mut Inner<S>, wakeups: &mut Wakeups) {
+        while !inner.done && inner.full == 0 {
+            let stream = &mut inner.stream;
+            let res = match waiters::poll(&self.waiters, wakeups,
+                                          || stream.poll()) {
+                Ok(Async::Ready(Some(item))) => Ok(item),
+                Ok(Async::Ready(None)) => {
+                    inner.done = true;
+                    wakeups.wake_all();
+                    break
+                }
+                Err(e) => Err(e),
+                Ok(Async::NotReady) => break,
+            };
+            for queue in inner.queues.iter_mut() {
+                if let Some(ref mut queue) = *queue {
+                    queue.push_back(res.clone());
+                    if queue.len() == inner.capacity {
+                        inner.full += 1;
+                    }
+                }
+            }
+            wakeups.wake_all();
+        }
+    }
+}
+
+impl<S> Stream for Fork<S>
+    where S: Stream,
+          S::Item: Clone,
+          S::Error: Clone,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        let mut wakeups = self.shared.waiters.wakeups();
+        // If user code panicked while another fork was polling the stream,
+        // the stream can't be polled any more, so this fork ends.
+        let mut inner = match self.shared.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return Ok(Async::Ready(None)),
+        };
+        let inner = &mut *inner;
+        for pulled in 0..2 {
+            let res = {
+                let queue = inner.queues[self.id].as_mut().unwrap();
+                let was_full = queue.len() == inner.capacity;
+                queue.pop_front().map(|res| (res, was_full))
+            };
+            if let Some((res, was_full)) = res {
+                if was_full {
+                    inner.full -= 1;
+                    wakeups.wake_all();
+                }
+                return res.map(|item| Async::Ready(Some(item)))
+            }
+            if pulled == 0 {
+                self.shared.pull(inner, &mut wakeups);
+            }
+        }
+        if inner.done {
+            return Ok(Async::Ready(None))
+        }
+        self.shared.waiters.register(self.id);
+        Ok(Async::NotReady)
+    }
+}
+
+impl<S> Drop for Fork<S>
+    where S: Stream,
+{
+    fn drop(&mut self) {
+        let mut wakeups = self.shared.waiters.wakeups();
+        self.shared.waiters.remove(self.id);
+        wakeups.wake_all();
+
+        // This may run while unwinding from a panic with the lock held, in
+        // which case the queues are left alone as they may be inconsistent.
+        let mut inner = match self.shared.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return,
+        };
+        let inner = &mut *inner;
+        if let Some(queue) = inner.queues[self.id].take() {
+            if queue.len() == inner.capacity {
+                inner.full -= 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::prelude::v1::*;
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use {Async, Poll};
+use stream::Stream;
+use super::waiters::{self, Waiters, Wakeups};
+
+/// A stream which splits the items of another stream into groups by key,
+/// yielding a sub-stream for each group.
+///
+/// This is created by the `Stream::group_by` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct GroupBy<S, K, F>
+    where S: Stream,
+          K: Hash + Eq,
+{
+    shared: Arc<Shared<S, K, F>>,
+}
+
+/// The stream of items in one group of a `GroupBy` stream.
+///
+/// This is yielded by `GroupBy` along with the key of the group, and yields
+/// the items of the underlying stream with that key. It ends once the
+/// underlying stream has ended.
+#[must_use = "streams do nothing unless polled"]
+pub struct SubStream<S, K, F>
+    where S: Stream,
+          K: Hash + Eq,
+{
+    shared: Arc<Shared<S, K, F>>,
+    key: K,
+    id: usize,
+}
+
+struct Shared<S, K, F>
+    where S: Stream,
+          K: Hash + Eq,
+{
+    inner: Mutex<Inner<S, K, F>>,
+    waiters: Arc<Waiters>,
+}
+
+struct Inner<S, K, F>
+    where S: Stream,
+          K: Hash + Eq,
+{
+    stream: S,
+    f: F,
+    limit: usize,
+    done: bool,
+    // An error to be yielded by the `GroupBy` stream.
+    err: Option<S::Error>,
+    groups: HashMap<K, Group<S::Item>>,
+    // Groups which haven't been yielded by the `GroupBy` stream yet.
+    new: VecDeque<K>,
+    // The number of groups whose buffer is full.
+    full: usize,
+    next_id: usize,
+    group_by_gone: bool,
+}
+
+struct Group<T> {
+    id: usize,
+    items: VecDeque<T>,
+    // Whether the sub-stream is gone, in which case items are discarded.
+    gone: bool,
+}
+
+// The id of the `GroupBy` stream among the waiters; sub-streams count up from
+// here.
+const GROUP_BY: usize = 0;
+
+pub fn new<S, K, F>(s: S, limit: usize, f: F) -> GroupBy<S, K, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> K,
+          K: Hash + Eq + Clone,
+{
+    assert!(limit > 0, "group_by buffer limit must be greater than zero");
+    GroupBy {
+        shared: Arc::new(Shared {
+            inner: Mutex::new(Inner {
+                stream: s,
+                f: f,
+                limit: limit,
+                done: false,
+                err: None,
+                groups: HashMap::new(),
+                new: VecDeque::new(),
+                full: 0,
+                next_id: GROUP_BY + 1,
+                group_by_gone: false,
+            }),
+            waiters: Arc::new(Waiters::new()),
+        }),
+    }
+}
+
+impl<S, K, F> Shared<S, K, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> K,
+          K: Hash + Eq + Clone,
+{
+    // Pulls items from the underlying stream and routes them to their groups,
+    // until it isn't ready or one of the buffers is full.
+    fn pull(&self, inner: &mut Inner<S, K, F>, wakeups: &mut Wakeups) {
+        while !inner.done &&
+              inner.err.is_none() &&
+              inner.full == 0 &&
+              inner.new.len() < inner.limit {
+            let stream = &mut inner.stream;
+            match waiters::poll(&self.waiters, wakeups, || stream.poll()) {
+                Ok(Async::Ready(Some(item))) => route(inner, item, wakeups),
+                Ok(Async::Ready(None)) => {
+                    inner.done = true;
+                    wakeups.wake_all();
+                }
+                Err(e) => {
+                    if !inner.group_by_gone {
+                        inner.err = Some(e);
+                        wakeups.wake(GROUP_BY);
+                    }
+                }
+                Ok(Async::NotReady) => break,
+            }
+        }
+    }
+}
+
+fn route<S, K, F>(inner: &mut Inner<S, K, F>,
+                  item: S::Item,
+                  wakeups: &mut Wakeups)
+    where S: Stream,
+          F: FnMut(&S::Item) -> K,
+          K: Hash + Eq + Clone,
+{
+    let key = (inner.f)(&item);
+    if let Some(group) = inner.groups.get_mut(&key) {
+        if !group.gone {
+            group.items.push_back(item);
+            if group.items.len() == inner.limit {
+                inner.full += 1;
+            }
+            wakeups.wake(group.id);
+        }
+        return
+    }
+
+    let id = inner.next_id;
+    inner.next_id += 1;
+    let mut group = Group {
+        id: id,
+        items: VecDeque::new(),
+        gone: inner.group_by_gone,
+    };
+    if !group.gone {
+        group.items.push_back(item);
+        if inner.limit == 1 {
+            inner.full += 1;
+        }
+        inner.new.push_back(key.clone());
+        wakeups.wake(GROUP_BY);
+    }
+    inner.groups.insert(key, group);
+}
+
+impl<S, K, F> Stream for GroupBy<S, K, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> K,
+          K: Hash + Eq + Clone,
+{
+    type Item = (K, SubStream<S, K, F>);
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, S::Error> {
+        let mut wakeups = self.shared.waiters.wakeups();
+        // If user code panicked while the lock was held, the groups can't be
+        // trusted any more, so the stream ends.
+        let mut inner = match self.shared.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return Ok(Async::Ready(None)),
+        };
+        let inner = &mut *inner;
+        for pulled in 0..2 {
+            if let Some(key) = inner.new.pop_front() {
+                if inner.new.len() + 1 == inner.limit {
+                    wakeups.wake_all();
+                }
+                let id = inner.groups[&key].id;
+                let sub = SubStream {
+                    shared: self.shared.clone(),
+                    key: key.clone(),
+                    id: id,
+                };
+                return Ok(Async::Ready(Some((key, sub))))
+            }
+            // Pulling stops at an error, so any new groups came before it.
+            if let Some(e) = inner.err.take() {
+                wakeups.wake_all();
+                return Err(e)
+            }
+            if pulled == 0 {
+                self.shared.pull(inner, &mut wakeups);
+            }
+        }
+        if inner.done {
+            return Ok(Async::Ready(None))
+        }
+        self.shared.waiters.register(GROUP_BY);
+        Ok(Async::NotReady)
+    }
+}
+
+impl<S, K, F> Drop for GroupBy<S, K, F>
+    where S: Stream,
+          K: Hash + Eq,
+{
+    fn drop(&mut self) {
+        let mut wakeups = self.shared.waiters.wakeups();
+        self.shared.waiters.remove(GROUP_BY);
+        wakeups.wake_all();
+
+        // This may run while unwinding from a panic with the lock held, in
+        // which case the groups are left alone as they may be inconsistent.
+        let mut inner = match self.shared.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return,
+        };
+        let inner = &mut *inner;
+        inner.group_by_gone = true;
+        inner.err = None;
+        for key in inner.new.drain(..) {
+            let group = inner.groups.get_mut(&key).unwrap();
+            if group.items.len() == inner.limit {
+                inner.full -= 1;
+            }
+            group.items.clear();
+            group.gone = true;
+        }
+    }
+}
+
+impl<S, K, F> SubStream<S, K, F>
+    where S: Stream,
+          K: Hash + Eq,
+{
+    /// Returns the key of the items in this group.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<S, K, F> Stream for SubStream<S, K, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> K,
+          K: Hash + Eq + Clone,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        let mut wakeups = self.shared.waiters.wakeups();
+        let mut inner = match self.shared.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return Ok(Async::Ready(None)),
+        };
+        let inner = &mut *inner;
+        for pulled in 0..2 {
+            let item = {
+                let group = inner.groups.get_mut(&self.key).unwrap();
+                let was_full = group.items.len() == inner.limit;
+                group.items.pop_front().map(|item| (item, was_full))
+            };
+            if let Some((item, was_full)) = item {
+                if was_full {
+                    inner.full -= 1;
+                    wakeups.wake_all();
+                }
+                return Ok(Async::Ready(Some(item)))
+            }
+            if pulled == 0 {
+                self.shared.pull(inner, &mut wakeups);
+            }
+        }
+        if inner.done {
+            return Ok(Async::Ready(None))
+        }
+        self.shared.waiters.register(self.id);
+        Ok(Async::NotReady)
+    }
+}
+
+impl<S, K, F> Drop for SubStream<S, K, F>
+    where S: Stream,
+          K: Hash + Eq,
+{
+    fn drop(&mut self) {
+        let mut wakeups = self.shared.waiters.wakeups();
+        self.shared.waiters.remove(self.id);
+        wakeups.wake_all();
+
+        let mut inner = match self.shared.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return,
+        };
+        let inner = &mut *inner;
+        if let Some(group) = inner.groups.get_mut(&self.key) {
+            if group.items.len() == inner.limit {
+                inner.full -= 1;
+            }
+            group.items.clear();
+            group.gone = true;
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use {Async, Poll};
+use stream::Stream;
+
+/// A stream combinator which calls a closure on each element of a stream
+/// before passing it on.
+///
+/// This is produced by the `Stream::inspect` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Inspect<S, F> {
+    stream: S,
+    f: F,
+}
+
+pub fn new<S, F>(s: S, f: F) -> Inspect<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item),
+{
+    Inspect {
+        stream: s,
+        f: f,
+    }
+}
+
+impl<S, F> Stream for Inspect<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item),
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        let option = try_ready!(self.stream.poll());
+        if let Some(ref item) = option {
+            (self.f)(item);
+        }
+        Ok(Async::Ready(option))
+    }
+}
@@ -0,0 +1,57 @@
+use core::cmp::Ordering;
+
+use {Async, Future, Poll};
+use stream::Stream;
+
+/// A future which finds the largest item of a stream according to a
+/// comparison function.
+///
+/// This future is created by the `Stream::max_by` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct MaxBy<S, F>
+    where S: Stream,
+{
+    stream: S,
+    compare: F,
+    max: Option<S::Item>,
+}
+
+pub fn new<S, F>(s: S, compare: F) -> MaxBy<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item, &S::Item) -> Ordering,
+{
+    MaxBy {
+        stream: s,
+        compare: compare,
+        max: None,
+    }
+}
+
+impl<S, F> Future for MaxBy<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item, &S::Item) -> Ordering,
+{
+    type Item = Option<S::Item>;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        loop {
+            match try!(self.stream.poll()) {
+                Async::Ready(Some(e)) => {
+                    // The last of several equal items is kept.
+                    let larger = match self.max {
+                        Some(ref max) => {
+                            (self.compare)(&e, max) != Ordering::Less
+                        }
+                        None => true,
+                    };
+                    if larger {
+                        self.max = Some(e);
+                    }
+                }
+                Async::Ready(None) => return Ok(Async::Ready(self.max.take())),
+                Async::NotReady => return Ok(Async::NotReady),
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::prelude::v1::*;
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use {Async, Poll};
+use stream::Stream;
+
+/// A stream which merges a number of sorted streams into one sorted stream.
+///
+/// This is created by the `stream::merge_sorted` function.
+#[must_use = "streams do nothing unless polled"]
+pub struct MergeSorted<S, K, F>
+    where S: Stream,
+{
+    streams: Vec<S>,
+    // The buffered next item of each stream, if it has been received.
+    heads: Vec<Option<S::Item>>,
+    // The keys of the buffered items, smallest first.
+    heap: BinaryHeap<Head<K>>,
+    // Streams which haven't ended and whose next item hasn't been received.
+    waiting: Vec<usize>,
+    key: F,
+}
+
+struct Head<K> {
+    key: K,
+    idx: usize,
+}
+
+/// Creates a stream which merges the sorted streams in `streams` into a
+/// single stream, sorted by the key which `key` computes for each item.
+///
+/// Each of the input streams must yield its items in increasing order of
+/// their keys. To be able to pick the smallest item, the returned stream
+/// waits until each input stream which hasn't ended has an item available,
+/// buffering one item per stream. Items with equal keys are yielded in the
+/// order of the streams they came from.
+///
+/// An error from any of the input streams is passed through immediately,
+/// after which that stream is polled again for its next item. The returned
+/// stream ends once all of the input streams have ended.
+///
+/// # Examples
+///
+/// ```
+/// use futures::Future;
+/// use futures::stream::{self, Stream};
+///
+/// let a = stream::iter(vec![Ok::<_, ()>((1, "a")), Ok((4, "a"))]);
+/// let b = stream::iter(vec![Ok((2, "b")), Ok((3, "b")), Ok((5, "b"))]);
+/// let merged = stream::merge_sorted(vec![a, b], |&(t, _)| t);
+/// assert_eq!(merged.map(|(t, _)| t).collect().wait(),
+///            Ok(vec![1, 2, 3, 4, 5]));
+/// ```
+pub fn merge_sorted<I, K, F>(streams: I, key: F)
+                             -> MergeSorted<I::Item, K, F>
+    where I: IntoIterator,
+          I::Item: Stream,
+          F: FnMut(&<I::Item as Stream>::Item) -> K,
+          K: Ord,
+{
+    let streams = streams.into_iter().collect::<Vec<_>>();
+    MergeSorted {
+        heads: streams.iter().map(|_| None).collect(),
+        heap: BinaryHeap::with_capacity(streams.len()),
+        waiting: (0..streams.len()).collect(),
+        streams: streams,
+        key: key,
+    }
+}
+
+impl<S, K, F> Stream for MergeSorted<S, K, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> K,
+          K: Ord,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        let mut i = 0;
+        while i < self.waiting.len() {
+            let idx = self.waiting[i];
+            match try!(self.streams[idx].poll()) {
+                Async::NotReady => i += 1,
+                Async::Ready(None) => {
+                    self.waiting.swap_remove(i);
+                }
+                Async::Ready(Some(item)) => {
+                    self.heap.push(Head {
+                        key: (self.key)(&item),
+                        idx: idx,
+                    });
+                    self.heads[idx] = Some(item);
+                    self.waiting.swap_remove(i);
+                }
+            }
+        }
+
+        // Until all the streams have an item we can't know which is smallest.
+        if !self.waiting.is_empty() {
+            return Ok(Async::NotReady)
+        }
+
+        match self.heap.pop() {
+            Some(head) => {
+                self.waiting.push(head.idx);
+                Ok(Async::Ready(self.heads[head.idx].take()))
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+// `BinaryHeap` is a max-heap, so heads are ordered in reverse to get the
+// smallest key first, with ties going to the earliest stream.
+impl<K: Ord> Ord for Head<K> {
+    fn cmp(&self, other: &Head<K>) -> Ordering {
+        other.key.cmp(&self.key).then(other.idx.cmp(&self.idx))
+    }
+}
+
+impl<K: Ord> PartialOrd for Head<K> {
+    fn partial_cmp(&self, other: &Head<K>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord> PartialEq for Head<K> {
+    fn eq(&self, other: &Head<K>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord> Eq for Head<K> {}
@@ -0,0 +1,57 @@
+use core::cmp::Ordering;
+
+use {Async, Future, Poll};
+use stream::Stream;
+
+/// A future which finds the smallest item of a stream according to a
+/// comparison function.
+///
+/// This future is created by the `Stream::min_by` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct MinBy<S, F>
+    where S: Stream,
+{
+    stream: S,
+    compare: F,
+    min: Option<S::Item>,
+}
+
+pub fn new<S, F>(s: S, compare: F) -> MinBy<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item, &S::Item) -> Ordering,
+{
+    MinBy {
+        stream: s,
+        compare: compare,
+        min: None,
+    }
+}
+
+impl<S, F> Future for MinBy<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item, &S::Item) -> Ordering,
+{
+    type Item = Option<S::Item>;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        loop {
+            match try!(self.stream.poll()) {
+                Async::Ready(Some(e)) => {
+                    // The first of several equal items is kept.
+                    let smaller = match self.min {
+                        Some(ref min) => {
+                            (self.compare)(&e, min) == Ordering::Less
+                        }
+                        None => true,
+                    };
+                    if smaller {
+                        self.min = Some(e);
+                    }
+                }
+                Async::Ready(None) => return Ok(Async::Ready(self.min.take())),
+                Async::NotReady => return Ok(Async::NotReady),
+            }
+        }
+    }
+}
@@ -16,8 +16,14 @@ use {IntoFuture, Poll};
 mod iter;
 pub use self::iter::{iter, IterStream};
 
+mod all;
 mod and_then;
+mod any;
+mod collect_into;
+mod concat;
+mod count;
 mod empty;
+mod enumerate;
 mod filter;
 mod filter_map;
 mod flatten;
@@ -25,18 +31,30 @@ mod fold;
 mod for_each;
 mod fuse;
 mod future;
+mod inspect;
 mod map;
 mod map_err;
+mod max_by;
 mod merge;
+mod min_by;
 mod or_else;
 mod peek;
+mod scan;
 mod skip;
 mod skip_while;
 mod take;
+mod take_until;
+mod take_while;
 mod then;
 mod zip;
+pub use self::all::All;
 pub use self::and_then::AndThen;
+pub use self::any::Any;
+pub use self::collect_into::CollectInto;
+pub use self::concat::Concat;
+pub use self::count::Count;
 pub use self::empty::{Empty, empty};
+pub use self::enumerate::Enumerate;
 pub use self::filter::Filter;
 pub use self::filter_map::FilterMap;
 pub use self::flatten::Flatten;
@@ -44,13 +62,19 @@ pub use self::fold::Fold;
 pub use self::for_each::ForEach;
 pub use self::fuse::Fuse;
 pub use self::future::StreamFuture;
+pub use self::inspect::Inspect;
 pub use self::map::Map;
 pub use self::map_err::MapErr;
+pub use self::max_by::MaxBy;
 pub use self::merge::{Merge, MergedItem};
+pub use self::min_by::MinBy;
 pub use self::or_else::OrElse;
+pub use self::scan::Scan;
 pub use self::skip::Skip;
 pub use self::skip_while::SkipWhile;
 pub use self::take::Take;
+pub use self::take_until::TakeUntil;
+pub use self::take_while::TakeWhile;
 pub use self::then::Then;
 pub use self::zip::Zip;
 pub use self::peek::Peekable;
@@ -62,18 +86,41 @@ if_std! {
     mod buffer_unordered;
     mod catch_unwind;
     mod channel;
+    mod chunks;
     mod collect;
+    mod for_each_concurrent;
+    mod fork;
+    mod group_by;
+    mod merge_sorted;
+    mod partition;
+    mod ready_chunks;
+    mod select_all;
+    mod split;
+    mod unzip;
     mod wait;
+    mod waiters;
     pub use self::buffered::Buffered;
     pub use self::buffer_unordered::BufferUnordered;
     pub use self::catch_unwind::CatchUnwind;
     pub use self::channel::{channel, Sender, Receiver, FutureSender};
+    pub use self::chunks::Chunks;
     pub use self::collect::Collect;
+    pub use self::for_each_concurrent::ForEachConcurrent;
+    pub use self::fork::Fork;
+    pub use self::group_by::{GroupBy, SubStream};
+    pub use self::merge_sorted::{merge_sorted, MergeSorted};
+    pub use self::partition::Partition;
+    pub use self::ready_chunks::ReadyChunks;
+    pub use self::select_all::{select_all, SelectAll};
+    pub use self::unzip::{UnzipLeft, UnzipRight};
     pub use self::wait::Wait;
 
     /// A type alias for `Box<Stream + Send>`
     pub type BoxStream<T, E> = ::std::boxed::Box<Stream<Item = T, Error = E> + Send>;
 
+    /// A type alias for `Box<Stream>`, without the `Send` bound of `BoxStream`
+    pub type LocalBoxStream<T, E> = ::std::boxed::Box<Stream<Item = T, Error = E>>;
+
     impl<S: ?Sized + Stream> Stream for ::std::boxed::Box<S> {
         type Item = S::Item;
         type Error = S::Error;
@@ -178,7 +225,7 @@ pub trait Stream {
     /// type inference as well by always returning a trait object. Note that
     /// this method requires the `Send` bound and returns a `BoxStream`, which
     /// also encodes this. If you'd like to create a `Box<Stream>` without the
-    /// `Send` bound, then the `Box::new` function can be used instead.
+    /// `Send` bound, then the `boxed_local` method can be used instead.
     ///
     /// # Examples
     ///
@@ -195,6 +242,30 @@ pub trait Stream {
         ::std::boxed::Box::new(self)
     }
 
+    /// Convenience function for turning this stream into a trait object which
+    /// isn't required to be `Send`.
+    ///
+    /// This is the same as `boxed`, except that it returns a
+    /// `LocalBoxStream` and so can be used for streams which can't be sent to
+    /// other threads, for example because they hold an `Rc`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use futures::stream::*;
+    ///
+    /// let (_tx, rx) = channel::<i32, i32>();
+    /// let rc = Rc::new(1);
+    /// let a: LocalBoxStream<i32, i32> = rx.map(move |i| i + *rc).boxed_local();
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn boxed_local(self) -> LocalBoxStream<Self::Item, Self::Error>
+        where Self: Sized + 'static,
+    {
+        ::std::boxed::Box::new(self)
+    }
+
     /// Converts this stream into a `Future`.
     ///
     /// A stream can be viewed as a future which will resolve to a pair containing
@@ -260,6 +331,34 @@ pub trait Stream {
         map_err::new(self, f)
     }
 
+    /// Does something with each item of this stream, passing the item on
+    /// unchanged.
+    ///
+    /// This is similar to `Iterator::inspect`: the closure is called with a
+    /// reference to each item as it's made available, which is typically
+    /// useful for debugging or logging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let mut seen = Vec::new();
+    /// let items = stream::iter::<_, i32, ()>(vec![Ok(1), Ok(2)])
+    ///     .inspect(|&i| seen.push(i))
+    ///     .collect()
+    ///     .wait();
+    /// assert_eq!(items, Ok(vec![1, 2]));
+    /// assert_eq!(seen, vec![1, 2]);
+    /// ```
+    fn inspect<F>(self, f: F) -> Inspect<Self, F>
+        where F: FnMut(&Self::Item),
+              Self: Sized
+    {
+        inspect::new(self, f)
+    }
+
     /// Filters the values produced by this stream according to the provided
     /// predicate.
     ///
@@ -489,6 +588,141 @@ pub trait Stream {
         collect::new(self)
     }
 
+    /// Collect all of the values of this stream into a collection of type
+    /// `C`, returning a future representing the result of that computation.
+    ///
+    /// This works like `collect`, but rather than a vector the collection can
+    /// be of any type implementing `Default` and `Extend`, such as a `HashMap`
+    /// or a `String`. The type usually needs to be given explicitly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let stream = stream::iter::<_, _, ()>(vec![Ok(("a", 1)), Ok(("b", 2))]);
+    /// let map = stream.collect_into::<HashMap<_, _>>().wait().unwrap();
+    /// assert_eq!(map["b"], 2);
+    /// ```
+    fn collect_into<C>(self) -> CollectInto<Self, C>
+        where C: Default + Extend<Self::Item>,
+              Self: Sized
+    {
+        collect_into::new(self)
+    }
+
+    /// Concatenates all of the items of this stream into one, returning a
+    /// future representing the result.
+    ///
+    /// This is useful for collections of items such as byte buffers or
+    /// strings, which are appended to the first item received with its
+    /// `Extend` implementation. If this stream has no items the result is the
+    /// default value of the item type. If an error happens then the items
+    /// received so far are dropped and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let chunks = vec![Ok::<_, ()>(vec![1, 2]), Ok(vec![]), Ok(vec![3])];
+    /// let bytes = stream::iter(chunks).concat().wait();
+    /// assert_eq!(bytes, Ok(vec![1, 2, 3]));
+    /// ```
+    fn concat(self) -> Concat<Self>
+        where Self::Item: Extend<<Self::Item as IntoIterator>::Item> +
+                          IntoIterator + Default,
+              Self: Sized
+    {
+        concat::new(self)
+    }
+
+    /// Counts the items of this stream, returning a future which resolves to
+    /// the count once this stream ends.
+    ///
+    /// If an error happens then it's returned instead.
+    fn count(self) -> Count<Self>
+        where Self: Sized
+    {
+        count::new(self)
+    }
+
+    /// Tests whether any item of this stream satisfies `pred`.
+    ///
+    /// The returned future resolves to `true` as soon as `pred` returns
+    /// `true` for an item, without waiting for the rest of this stream, or
+    /// to `false` once this stream ends. If an error happens first then it's
+    /// returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let stream = stream::iter::<_, i32, ()>((1..4).map(Ok));
+    /// assert_eq!(stream.any(|i| i > 2).wait(), Ok(true));
+    /// ```
+    fn any<P>(self, pred: P) -> Any<Self, P>
+        where P: FnMut(Self::Item) -> bool,
+              Self: Sized
+    {
+        any::new(self, pred)
+    }
+
+    /// Tests whether every item of this stream satisfies `pred`.
+    ///
+    /// The returned future resolves to `false` as soon as `pred` returns
+    /// `false` for an item, without waiting for the rest of this stream, or
+    /// to `true` once this stream ends. If an error happens first then it's
+    /// returned instead.
+    fn all<P>(self, pred: P) -> All<Self, P>
+        where P: FnMut(Self::Item) -> bool,
+              Self: Sized
+    {
+        all::new(self, pred)
+    }
+
+    /// Finds the smallest item of this stream according to `compare`,
+    /// returning a future which resolves to it once this stream ends.
+    ///
+    /// The future resolves to `None` if this stream has no items. If several
+    /// items are equally small, the first of them is returned. If an error
+    /// happens then it's returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let stream = stream::iter::<_, _, ()>(vec![Ok("bb"), Ok("a"), Ok("c")]);
+    /// assert_eq!(stream.min_by(|a, b| a.len().cmp(&b.len())).wait(),
+    ///            Ok(Some("a")));
+    /// ```
+    fn min_by<F>(self, compare: F) -> MinBy<Self, F>
+        where F: FnMut(&Self::Item, &Self::Item) -> ::core::cmp::Ordering,
+              Self: Sized
+    {
+        min_by::new(self, compare)
+    }
+
+    /// Finds the largest item of this stream according to `compare`,
+    /// returning a future which resolves to it once this stream ends.
+    ///
+    /// The future resolves to `None` if this stream has no items. If several
+    /// items are equally large, the last of them is returned. If an error
+    /// happens then it's returned instead.
+    fn max_by<F>(self, compare: F) -> MaxBy<Self, F>
+        where F: FnMut(&Self::Item, &Self::Item) -> ::core::cmp::Ordering,
+              Self: Sized
+    {
+        max_by::new(self, compare)
+    }
+
     /// Execute an accumulating computation over a stream, collecting all the
     /// values into one final result.
     ///
@@ -533,6 +767,38 @@ pub trait Stream {
         fold::new(self, f, init)
     }
 
+    /// Maps the elements of this stream while threading a piece of state
+    /// through, ending the stream early if requested.
+    ///
+    /// This is similar to `Iterator::scan`. The closure is given mutable
+    /// access to the state, which starts out as `initial_state`, along with
+    /// each element of this stream, and returns a future. If the future
+    /// resolves to `Some` then its value is yielded from the returned stream,
+    /// and if it resolves to `None` the returned stream ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let sums = stream::iter::<_, i32, ()>((1..10).map(Ok))
+    ///     .scan(0, |sum, i| {
+    ///         *sum += i;
+    ///         Ok(if *sum < 10 { Some(*sum) } else { None })
+    ///     })
+    ///     .collect()
+    ///     .wait();
+    /// assert_eq!(sums, Ok(vec![1, 3, 6]));
+    /// ```
+    fn scan<T, F, R, B>(self, initial_state: T, f: F) -> Scan<Self, T, F, R>
+        where F: FnMut(&mut T, Self::Item) -> R,
+              R: IntoFuture<Item=Option<B>, Error=Self::Error>,
+              Self: Sized
+    {
+        scan::new(self, initial_state, f)
+    }
+
     /// Flattens a stream of streams into just one continuous stream.
     ///
     /// If this stream's elements are themselves streams then this combinator
@@ -580,6 +846,70 @@ pub trait Stream {
         skip_while::new(self, pred)
     }
 
+    /// Take elements from this stream while the predicate provided resolves
+    /// to `true`.
+    ///
+    /// This function, like `Iterator::take_while`, will take elements from
+    /// the stream until the `predicate` resolves to `false`. Once one element
+    /// returns false the returned stream ends, and that element is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let items = stream::iter::<_, i32, ()>((1..10).map(Ok))
+    ///     .take_while(|&i| Ok(i < 4))
+    ///     .collect()
+    ///     .wait();
+    /// assert_eq!(items, Ok(vec![1, 2, 3]));
+    /// ```
+    fn take_while<P, R>(self, pred: P) -> TakeWhile<Self, P, R>
+        where P: FnMut(&Self::Item) -> R,
+              R: IntoFuture<Item=bool, Error=Self::Error>,
+              Self: Sized
+    {
+        take_while::new(self, pred)
+    }
+
+    /// Take elements from this stream until the future provided resolves.
+    ///
+    /// The returned stream passes the elements of this stream through until
+    /// `until` completes successfully, at which point it ends. This is useful
+    /// to stop processing a stream once a shutdown signal arrives. If `until`
+    /// fails, its error is returned from the stream, which also ends it.
+    ///
+    /// The future is always checked before this stream, so no more elements
+    /// are taken once it has completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::{Future, oneshot};
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let (shutdown, signal) = oneshot::<()>();
+    /// let mut seen = 0;
+    /// let items = stream::iter::<_, i32, _>((1..10).map(Ok))
+    ///     .take_until(signal)
+    ///     .inspect(|_| seen += 1)
+    ///     .wait();
+    /// let mut shutdown = Some(shutdown);
+    /// for item in items {
+    ///     if item.unwrap() == 3 {
+    ///         shutdown.take().unwrap().complete(());
+    ///     }
+    /// }
+    /// assert_eq!(seen, 3);
+    /// ```
+    fn take_until<F>(self, until: F) -> TakeUntil<Self, F::Future>
+        where F: IntoFuture<Error=Self::Error>,
+              Self: Sized
+    {
+        take_until::new(self, until)
+    }
+
     /// Runs this stream to completion, executing the provided closure for each
     /// element on the stream.
     ///
@@ -598,6 +928,48 @@ pub trait Stream {
         for_each::new(self, f)
     }
 
+    /// Runs this stream to completion, executing the provided closure for each
+    /// element on the stream and running up to `limit` of the futures it
+    /// returns at once.
+    ///
+    /// Unlike `for_each`, the closure returns a future, which is run
+    /// concurrently with those for other items. Once `limit` futures are
+    /// running, no more items are taken from this stream until one of them
+    /// has completed.
+    ///
+    /// The returned future resolves to `()` once this stream has ended and
+    /// all the futures have completed. Any error on the stream or in one of
+    /// the futures will cause the returned future to resolve to that error
+    /// immediately, dropping all the futures which are still running.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let mut sum = 0;
+    /// let stream = stream::iter::<_, i32, ()>((1..6).map(Ok));
+    /// stream.for_each_concurrent(2, |i| {
+    ///     sum += i;
+    ///     Ok(())
+    /// }).wait().unwrap();
+    /// assert_eq!(sum, 15);
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn for_each_concurrent<F, U>(self, limit: usize, f: F)
+                                 -> ForEachConcurrent<Self, F, U>
+        where F: FnMut(Self::Item) -> U,
+              U: IntoFuture<Item = (), Error = Self::Error>,
+              Self: Sized
+    {
+        for_each_concurrent::new(self, limit, f)
+    }
+
     /// Creates a new stream of at most `amt` items.
     ///
     /// Once `amt` items have been yielded from this stream then it will always
@@ -711,6 +1083,211 @@ pub trait Stream {
         buffer_unordered::new(self, amt)
     }
 
+    /// An adaptor for collecting the items of this stream into batches of
+    /// `capacity` items.
+    ///
+    /// The returned stream yields a `Vec` each time `capacity` items have been
+    /// received from this stream. Once this stream ends, any remaining items
+    /// are yielded as a final, smaller batch.
+    ///
+    /// If this stream yields an error, the items received before it are
+    /// yielded first as a smaller batch, and the error is returned on the
+    /// following poll, so no items are lost.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let stream = stream::iter::<_, i32, ()>((1..6).map(Ok));
+    /// let chunks = stream.chunks(2).collect().wait().unwrap();
+    /// assert_eq!(chunks, vec![vec![1, 2], vec![3, 4], vec![5]]);
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn chunks(self, capacity: usize) -> Chunks<Self>
+        where Self: Sized
+    {
+        chunks::new(self, capacity)
+    }
+
+    /// An adaptor for collecting the items of this stream which are
+    /// immediately available into batches of at most `capacity` items.
+    ///
+    /// This is like `chunks`, except that the returned stream doesn't wait
+    /// for a full batch: whenever this stream isn't ready to yield another
+    /// item, the items received so far are yielded as a batch. Batches are
+    /// never empty, so the returned stream is only `NotReady` if no items are
+    /// available at all.
+    ///
+    /// Errors and the end of the stream are handled as with `chunks`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `capacity` is zero.
+    #[cfg(feature = "use_std")]
+    fn ready_chunks(self, capacity: usize) -> ReadyChunks<Self>
+        where Self: Sized
+    {
+        ready_chunks::new(self, capacity)
+    }
+
+    /// An adaptor for splitting the items of this stream into groups by the
+    /// key which `f` computes for each item.
+    ///
+    /// The returned stream yields a pair of the key and a `SubStream` the
+    /// first time an item with a new key is received, and every item with
+    /// that key is routed to that sub-stream. The sub-streams may be handed
+    /// off to other tasks: whichever of them, or the returned stream, is
+    /// polled receives items from this stream on behalf of all of them. All
+    /// the sub-streams end once this stream ends, while errors are yielded
+    /// by the returned stream.
+    ///
+    /// Each group buffers at most `limit` items which haven't been taken by
+    /// its sub-stream yet, and at most `limit` new groups are buffered until
+    /// they're taken from the returned stream. While any of these buffers is
+    /// full, this stream isn't polled, so a slow consumer slows down all of
+    /// them. Items of a group whose sub-stream has been dropped, and items of
+    /// new groups once the returned stream has been dropped, are discarded.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let stream = stream::iter::<_, i32, ()>((1..7).map(Ok));
+    /// let mut groups = stream.group_by(10, |i| i % 3).wait();
+    /// let (key, ones) = groups.next().unwrap().unwrap();
+    /// assert_eq!(key, 1);
+    /// let (key, twos) = groups.next().unwrap().unwrap();
+    /// assert_eq!(key, 2);
+    /// assert_eq!(ones.collect().wait(), Ok(vec![1, 4]));
+    /// assert_eq!(twos.collect().wait(), Ok(vec![2, 5]));
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn group_by<K, F>(self, limit: usize, f: F) -> GroupBy<Self, K, F>
+        where F: FnMut(&Self::Item) -> K,
+              K: ::std::hash::Hash + Eq + Clone,
+              Self: Sized
+    {
+        group_by::new(self, limit, f)
+    }
+
+    /// Shares the items of this stream among `n` streams, each of which
+    /// yields every item and error of this stream.
+    ///
+    /// The returned streams may be consumed independently, for example by
+    /// different tasks. Whichever of them is polled receives items from this
+    /// stream on behalf of all of them, buffering at most `capacity` items
+    /// for each of them which it hasn't yielded yet. Once the buffer of any
+    /// of the streams is full, this stream isn't polled until that stream
+    /// catches up, so the slowest of them determines the pace of all of
+    /// them. A stream which is dropped no longer receives items and doesn't
+    /// hold back the others.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let stream = stream::iter::<_, i32, ()>((1..4).map(Ok));
+    /// let mut forks = stream.fork(2, 10);
+    /// let b = forks.pop().unwrap();
+    /// let a = forks.pop().unwrap();
+    /// assert_eq!(a.collect().wait(), Ok(vec![1, 2, 3]));
+    /// assert_eq!(b.map(|i| i * 2).collect().wait(), Ok(vec![2, 4, 6]));
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn fork(self, n: usize, capacity: usize) -> ::std::vec::Vec<Fork<Self>>
+        where Self::Item: Clone,
+              Self::Error: Clone,
+              Self: Sized
+    {
+        fork::new(self, n, capacity)
+    }
+
+    /// Splits this stream into two streams, the first yielding the items for
+    /// which `pred` returns `true` and the second those for which it returns
+    /// `false`.
+    ///
+    /// The two streams may be consumed independently, for example by
+    /// different tasks. Whichever of them is polled receives items from this
+    /// stream on behalf of both, buffering at most `capacity` items for the
+    /// other one. Once that buffer is full, this stream isn't polled until
+    /// the other stream catches up. Items for a stream which has been dropped
+    /// are discarded.
+    ///
+    /// Errors aren't buffered, but are yielded by whichever of the two
+    /// streams receives them from this stream.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let stream = stream::iter::<_, i32, ()>((1..7).map(Ok));
+    /// let (even, odd) = stream.partition(10, |i| i % 2 == 0);
+    /// assert_eq!(odd.collect().wait(), Ok(vec![1, 3, 5]));
+    /// assert_eq!(even.collect().wait(), Ok(vec![2, 4, 6]));
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn partition<P>(self, capacity: usize, pred: P)
+                    -> (Partition<Self, P>, Partition<Self, P>)
+        where P: FnMut(&Self::Item) -> bool,
+              Self: Sized
+    {
+        partition::new(self, capacity, pred)
+    }
+
+    /// Splits this stream of pairs into two streams, the first yielding the
+    /// first element of each pair and the second the second element.
+    ///
+    /// This is the opposite of `zip`. The two streams share this stream in
+    /// the same way as those returned by `partition`, including the limit of
+    /// `capacity` buffered items for each of them and the handling of errors.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let stream = stream::iter::<_, _, ()>(vec![Ok((1, 'a')), Ok((2, 'b'))]);
+    /// let (numbers, letters) = stream.unzip(10);
+    /// assert_eq!(letters.collect().wait(), Ok(vec!['a', 'b']));
+    /// assert_eq!(numbers.collect().wait(), Ok(vec![1, 2]));
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn unzip<A, B>(self, capacity: usize)
+                   -> (UnzipLeft<Self, A, B>, UnzipRight<Self, A, B>)
+        where Self: Stream<Item = (A, B)> + Sized
+    {
+        unzip::new(self, capacity)
+    }
+
     /// An adapter for merging the output of two streams.
     ///
     /// The merged stream produces items from one or both of the underlying
@@ -735,6 +1312,29 @@ pub trait Stream {
         zip::new(self, other)
     }
 
+    /// Creates a stream which yields each element of this stream along with
+    /// its index, starting from 0.
+    ///
+    /// This is similar to `Iterator::enumerate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::Future;
+    /// use futures::stream::{self, Stream};
+    ///
+    /// let items = stream::iter::<_, char, ()>(vec![Ok('a'), Ok('b')])
+    ///     .enumerate()
+    ///     .collect()
+    ///     .wait();
+    /// assert_eq!(items, Ok(vec![(0, 'a'), (1, 'b')]));
+    /// ```
+    fn enumerate(self) -> Enumerate<Self>
+        where Self: Sized
+    {
+        enumerate::new(self)
+    }
+
     /// Creates a new stream witch exposes a `peek` method.
     ///
     /// Calling `peek` returns a reference to the next item in the stream.
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use Poll;
+use stream::Stream;
+use super::split::{self, Shared, Split};
+
+/// One of the two streams into which another stream is split by a predicate.
+///
+/// This is created by the `Stream::partition` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Partition<S, P>
+    where S: Stream,
+          P: FnMut(&S::Item) -> bool,
+{
+    shared: Arc<Shared<S, Pred<P>>>,
+    matches: bool,
+}
+
+struct Pred<P>(P);
+
+impl<T, P> Split<T> for Pred<P>
+    where P: FnMut(&T) -> bool,
+{
+    type Left = T;
+    type Right = T;
+
+    fn split(&mut self, item: T) -> (Option<T>, Option<T>) {
+        if (self.0)(&item) {
+            (Some(item), None)
+        } else {
+            (None, Some(item))
+        }
+    }
+}
+
+pub fn new<S, P>(s: S, capacity: usize, pred: P)
+                 -> (Partition<S, P>, Partition<S, P>)
+    where S: Stream,
+          P: FnMut(&S::Item) -> bool,
+{
+    let shared = split::new(s, capacity, Pred(pred));
+    let yes = Partition { shared: shared.clone(), matches: true };
+    let no = Partition { shared: shared, matches: false };
+    (yes, no)
+}
+
+impl<S, P> Stream for Partition<S, P>
+    where S: Stream,
+          P: FnMut(&S::Item) -> bool,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        if self.matches {
+            self.shared.poll_left()
+        } else {
+            self.shared.poll_right()
+        }
+    }
+}
+
+impl<S, P> Drop for Partition<S, P>
+    where S: Stream,
+          P: FnMut(&S::Item) -> bool,
+{
+    fn drop(&mut self) {
+        if self.matches {
+            self.shared.drop_left()
+        } else {
+            self.shared.drop_right()
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::prelude::v1::*;
+
+use std::mem;
+
+use {Async, Poll};
+use stream::{Stream, Fuse};
+
+/// An adaptor for a stream which collects the items which are immediately
+/// available into batches.
+///
+/// This structure is produced by the `Stream::ready_chunks` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct ReadyChunks<S>
+    where S: Stream,
+{
+    stream: Fuse<S>,
+    items: Vec<S::Item>,
+    err: Option<S::Error>,
+    cap: usize,
+}
+
+pub fn new<S>(s: S, capacity: usize) -> ReadyChunks<S>
+    where S: Stream,
+{
+    assert!(capacity > 0, "chunk size must be greater than zero");
+    ReadyChunks {
+        stream: super::fuse::new(s),
+        items: Vec::with_capacity(capacity),
+        err: None,
+        cap: capacity,
+    }
+}
+
+impl<S> ReadyChunks<S>
+    where S: Stream,
+{
+    fn take(&mut self) -> Vec<S::Item> {
+        let cap = self.cap;
+        mem::replace(&mut self.items, Vec::with_capacity(cap))
+    }
+}
+
+impl<S> Stream for ReadyChunks<S>
+    where S: Stream,
+{
+    type Item = Vec<S::Item>;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Some(err) = self.err.take() {
+            return Err(err)
+        }
+        loop {
+            match self.stream.poll() {
+                // Unlike `Chunks`, don't wait for more items if we've got
+                // some already.
+                Ok(Async::NotReady) => {
+                    if self.items.is_empty() {
+                        return Ok(Async::NotReady)
+                    }
+                    return Ok(Async::Ready(Some(self.take())))
+                }
+                Ok(Async::Ready(Some(item))) => {
+                    self.items.push(item);
+                    if self.items.len() >= self.cap {
+                        return Ok(Async::Ready(Some(self.take())))
+                    }
+                }
+                Ok(Async::Ready(None)) => {
+                    if self.items.is_empty() {
+                        return Ok(Async::Ready(None))
+                    }
+                    return Ok(Async::Ready(Some(self.take())))
+                }
+                Err(e) => {
+                    if self.items.is_empty() {
+                        return Err(e)
+                    }
+                    self.err = Some(e);
+                    return Ok(Async::Ready(Some(self.take())))
+                }
+            }
+        }
+    }
+}
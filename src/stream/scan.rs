@@ -0,0 +1,66 @@
+use {Async, Poll, IntoFuture, Future};
+use stream::Stream;
+
+/// A stream combinator which maps elements of a stream while threading a
+/// piece of state through, similar to `Iterator::scan`.
+///
+/// This structure is produced by the `Stream::scan` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Scan<S, T, F, R> where R: IntoFuture {
+    stream: S,
+    state: T,
+    f: F,
+    pending: Option<R::Future>,
+    done: bool,
+}
+
+pub fn new<S, T, F, R, B>(s: S, initial_state: T, f: F) -> Scan<S, T, F, R>
+    where S: Stream,
+          F: FnMut(&mut T, S::Item) -> R,
+          R: IntoFuture<Item=Option<B>, Error=S::Error>,
+{
+    Scan {
+        stream: s,
+        state: initial_state,
+        f: f,
+        pending: None,
+        done: false,
+    }
+}
+
+impl<S, T, F, R, B> Stream for Scan<S, T, F, R>
+    where S: Stream,
+          F: FnMut(&mut T, S::Item) -> R,
+          R: IntoFuture<Item=Option<B>, Error=S::Error>,
+{
+    type Item = B;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<B>, S::Error> {
+        if self.done {
+            return Ok(Async::Ready(None))
+        }
+
+        if self.pending.is_none() {
+            let item = match try_ready!(self.stream.poll()) {
+                Some(e) => e,
+                None => return Ok(Async::Ready(None)),
+            };
+            self.pending = Some((self.f)(&mut self.state, item).into_future());
+        }
+
+        let result = self.pending.as_mut().unwrap().poll();
+        if let Ok(Async::NotReady) = result {
+            return Ok(Async::NotReady)
+        }
+        self.pending = None;
+        match try!(result) {
+            Async::Ready(Some(b)) => Ok(Async::Ready(Some(b))),
+            Async::Ready(None) => {
+                self.done = true;
+                Ok(Async::Ready(None))
+            }
+            Async::NotReady => unreachable!(),
+        }
+    }
+}
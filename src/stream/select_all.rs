@@ -0,0 +1,175 @@
+use std::prelude::v1::*;
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use {Async, Poll};
+use stream::Stream;
+use task::{self, EventSet, ReadyQueue, UnparkEvent};
+
+/// A stream which merges an arbitrary number of streams of the same type.
+///
+/// Items are yielded as they become available, taking turns between the
+/// streams which are ready so that a busy stream can't starve the others.
+/// Only streams which have been unparked since they last returned `NotReady`
+/// are polled again. This is created by the `stream::select_all` function,
+/// or by `SelectAll::new` and `push`.
+#[must_use = "streams do nothing unless polled"]
+pub struct SelectAll<S> {
+    streams: Vec<Slot<S>>,
+    free: Vec<usize>,
+    len: usize,
+    // Streams to poll, in order. Each stream appears here at most once.
+    pending: VecDeque<usize>,
+    // Streams which have been unparked since they returned `NotReady`.
+    unparked: Arc<ReadyQueue>,
+}
+
+struct Slot<S> {
+    stream: Option<S>,
+    queued: bool,
+}
+
+/// Creates a stream which merges all the streams in `streams`.
+///
+/// The returned stream yields the items of all the input streams, in the
+/// order they become available, and an error whenever one of the input
+/// streams fails. A stream which fails is polled again afterwards, as with
+/// any other stream. The returned stream ends once all of the input streams
+/// have ended, and more streams can be added with `SelectAll::push`.
+///
+/// Polling is fair: when several streams are ready, each of them yields an
+/// item in turn.
+///
+/// # Examples
+///
+/// ```
+/// use futures::Future;
+/// use futures::stream::{self, Stream};
+///
+/// let a = stream::iter(vec![Ok::<_, ()>(1), Ok(2)]);
+/// let b = stream::iter(vec![Ok(3), Ok(4)]);
+/// let merged = stream::select_all(vec![a, b]);
+/// assert_eq!(merged.collect().wait(), Ok(vec![1, 3, 2, 4]));
+/// ```
+pub fn select_all<I>(streams: I) -> SelectAll<I::Item>
+    where I: IntoIterator,
+          I::Item: Stream,
+{
+    let mut set = SelectAll::new();
+    for stream in streams {
+        set.push(stream);
+    }
+    set
+}
+
+impl<S: Stream> SelectAll<S> {
+    /// Creates an empty set of streams.
+    ///
+    /// The returned stream ends immediately unless streams are added with
+    /// `push` before it's polled.
+    pub fn new() -> SelectAll<S> {
+        SelectAll {
+            streams: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            pending: VecDeque::new(),
+            unparked: Arc::new(ReadyQueue::new()),
+        }
+    }
+
+    /// Adds a stream to this set.
+    ///
+    /// The stream is polled the next time this set is polled. Note that this
+    /// method doesn't unpark the task polling this set, so if this is called
+    /// from outside that task it's the caller's responsibility to make sure
+    /// the set is polled again.
+    pub fn push(&mut self, stream: S) {
+        let slot = Slot { stream: Some(stream), queued: true };
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.streams[idx] = slot;
+                idx
+            }
+            None => {
+                self.streams.push(slot);
+                self.streams.len() - 1
+            }
+        };
+        self.pending.push_back(idx);
+        self.len += 1;
+    }
+
+    /// Returns the number of streams in this set which haven't ended yet.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether all the streams in this set have ended.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn queue(&mut self, idx: usize) {
+        // An id may refer to a stream which has since ended, in which case
+        // it's ignored, or to another stream which reused its slot, in which
+        // case that stream is just polled once more than necessary.
+        let slot = &mut self.streams[idx];
+        if slot.stream.is_some() && !slot.queued {
+            slot.queued = true;
+            self.pending.push_back(idx);
+        }
+    }
+}
+
+impl<S: Stream> Stream for SelectAll<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        for idx in self.unparked.drain() {
+            self.queue(idx);
+        }
+
+        while let Some(idx) = self.pending.pop_front() {
+            let event = UnparkEvent::new(self.unparked.clone() as Arc<EventSet>,
+                                         idx);
+            let res = {
+                let stream = self.streams[idx].stream.as_mut().unwrap();
+                task::with_unpark_event(event, || stream.poll())
+            };
+            match res {
+                Ok(Async::NotReady) => {
+                    self.streams[idx].queued = false;
+                }
+                Ok(Async::Ready(None)) => {
+                    self.streams[idx] = Slot { stream: None, queued: false };
+                    self.free.push(idx);
+                    self.len -= 1;
+                }
+                // The stream may have more to give, so it goes to the back
+                // of the queue to let the others have their turn first.
+                Ok(Async::Ready(Some(item))) => {
+                    self.pending.push_back(idx);
+                    return Ok(Async::Ready(Some(item)))
+                }
+                Err(e) => {
+                    self.pending.push_back(idx);
+                    return Err(e)
+                }
+            }
+        }
+
+        if self.len == 0 {
+            Ok(Async::Ready(None))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+impl<S: Stream> Default for SelectAll<S> {
+    fn default() -> SelectAll<S> {
+        SelectAll::new()
+    }
+}
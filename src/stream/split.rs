@@ -0,0 +1,173 @@
+use std::prelude::v1::*;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use {Async, Poll};
+use stream::Stream;
+use super::waiters::{self, Waiters, Wakeups};
+
+// The state shared between the two halves of a stream which is split in two,
+// such as by `partition` or `unzip`.
+//
+// Whichever half is polled receives items from the stream on behalf of both,
+// splitting each of them with `D` and buffering the parts for the other half.
+// Errors aren't buffered, but are yielded by the half which received them.
+pub struct Shared<S, D>
+    where S: Stream,
+          D: Split<S::Item>,
+{
+    inner: Mutex<Inner<S, D>>,
+    waiters: Arc<Waiters>,
+}
+
+// Splits an item into the parts for each half, if any.
+pub trait Split<T> {
+    type Left;
+    type Right;
+
+    fn split(&mut self, item: T) -> (Option<Self::Left>, Option<Self::Right>);
+}
+
+struct Inner<S, D>
+    where S: Stream,
+          D: Split<S::Item>,
+{
+    stream: S,
+    split: D,
+    capacity: usize,
+    done: bool,
+    // The parts not yet taken by each half, or `None` once it's dropped.
+    left: Option<VecDeque<D::Left>>,
+    right: Option<VecDeque<D::Right>>,
+}
+
+const LEFT: usize = 0;
+const RIGHT: usize = 1;
+
+pub fn new<S, D>(s: S, capacity: usize, split: D) -> Arc<Shared<S, D>>
+    where S: Stream,
+          D: Split<S::Item>,
+{
+    assert!(capacity > 0, "split capacity must be greater than zero");
+    Arc::new(Shared {
+        inner: Mutex::new(Inner {
+            stream: s,
+            split: split,
+            capacity: capacity,
+            done: false,
+            left: Some(VecDeque::new()),
+            right: Some(VecDeque::new()),
+        }),
+        waiters: Arc::new(Waiters::new()),
+    })
+}
+
+impl<S, D> Shared<S, D>
+    where S: Stream,
+          D: Split<S::Item>,
+{
+    pub fn poll_left(&self) -> Poll<Option<D::Left>, S::Error> {
+        self.poll(LEFT, |inner| &mut inner.left)
+    }
+
+    pub fn poll_right(&self) -> Poll<Option<D::Right>, S::Error> {
+        self.poll(RIGHT, |inner| &mut inner.right)
+    }
+
+    pub fn drop_left(&self) {
+        self.drop_half(LEFT, |inner| &mut inner.left)
+    }
+
+    pub fn drop_right(&self) {
+        self.drop_half(RIGHT, |inner| &mut inner.right)
+    }
+
+    fn poll<T, Q>(&self, id: usize, queue: Q) -> Poll<Option<T>, S::Error>
+        where Q: Fn(&mut Inner<S, D>) -> &mut Option<VecDeque<T>>
+    {
+        let mut wakeups = self.waiters.wakeups();
+        // If the other half panicked while polling the stream, the stream
+        // can't be polled any more, so this half ends too.
+        let mut inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return Ok(Async::Ready(None)),
+        };
+        let inner = &mut *inner;
+        loop {
+            let capacity = inner.capacity;
+            let (item, was_full) = {
+                let queue = queue(inner).as_mut().unwrap();
+                let was_full = queue.len() == capacity;
+                (queue.pop_front(), was_full)
+            };
+            if let Some(item) = item {
+                if was_full {
+                    wakeups.wake_all();
+                }
+                return Ok(Async::Ready(Some(item)))
+            }
+            if inner.done {
+                return Ok(Async::Ready(None))
+            }
+
+            // Items are only pulled until one arrives for this half, so that
+            // an error can't overtake an item buffered for it.
+            if inner.is_full() || !try!(self.pull(inner, &mut wakeups)) {
+                self.waiters.register(id);
+                return Ok(Async::NotReady)
+            }
+        }
+    }
+
+    // Pulls an item from the stream into the queues of the two halves,
+    // returning whether the stream was ready.
+    fn pull(&self, inner: &mut Inner<S, D>, wakeups: &mut Wakeups)
+            -> Result<bool, S::Error> {
+        let item = {
+            let stream = &mut inner.stream;
+            let poll = waiters::poll(&self.waiters, wakeups, || stream.poll());
+            match try!(poll) {
+                Async::Ready(Some(item)) => item,
+                Async::Ready(None) => {
+                    inner.done = true;
+                    wakeups.wake_all();
+                    return Ok(true)
+                }
+                Async::NotReady => return Ok(false),
+            }
+        };
+        let (left, right) = inner.split.split(item);
+        if let (Some(left), Some(queue)) = (left, inner.left.as_mut()) {
+            queue.push_back(left);
+            wakeups.wake(LEFT);
+        }
+        if let (Some(right), Some(queue)) = (right, inner.right.as_mut()) {
+            queue.push_back(right);
+            wakeups.wake(RIGHT);
+        }
+        Ok(true)
+    }
+
+    fn drop_half<T, Q>(&self, id: usize, queue: Q)
+        where Q: Fn(&mut Inner<S, D>) -> &mut Option<VecDeque<T>>
+    {
+        let mut wakeups = self.waiters.wakeups();
+        // This may run while unwinding from a panic in the other half, so a
+        // poisoned lock mustn't panic again.
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        *queue(&mut inner) = None;
+        self.waiters.remove(id);
+        wakeups.wake_all();
+    }
+}
+
+impl<S, D> Inner<S, D>
+    where S: Stream,
+          D: Split<S::Item>,
+{
+    fn is_full(&self) -> bool {
+        self.left.as_ref().is_some_and(|q| q.len() >= self.capacity) ||
+            self.right.as_ref().is_some_and(|q| q.len() >= self.capacity)
+    }
+}
@@ -0,0 +1,51 @@
+use {Async, Poll, IntoFuture, Future};
+use stream::Stream;
+
+/// A stream combinator which takes elements from a stream until a future
+/// resolves.
+///
+/// This structure is produced by the `Stream::take_until` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct TakeUntil<S, F> {
+    stream: S,
+    until: Option<F>,
+}
+
+pub fn new<S, F>(s: S, f: F) -> TakeUntil<S, F::Future>
+    where S: Stream,
+          F: IntoFuture<Error=S::Error>,
+{
+    TakeUntil {
+        stream: s,
+        until: Some(f.into_future()),
+    }
+}
+
+impl<S, F> Stream for TakeUntil<S, F>
+    where S: Stream,
+          F: Future<Error=S::Error>,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        // The future is checked first so that once it has resolved no more
+        // items are taken, even if the stream has some ready. If it fails the
+        // error is yielded once, and the stream ends after it.
+        let res = match self.until {
+            Some(ref mut until) => until.poll(),
+            None => return Ok(Async::Ready(None)),
+        };
+        match res {
+            Ok(Async::NotReady) => self.stream.poll(),
+            Ok(Async::Ready(_)) => {
+                self.until = None;
+                Ok(Async::Ready(None))
+            }
+            Err(e) => {
+                self.until = None;
+                Err(e)
+            }
+        }
+    }
+}
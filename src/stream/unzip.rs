@@ -0,0 +1,83 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use Poll;
+use stream::Stream;
+use super::split::{self, Shared, Split};
+
+/// The stream of the first elements of the pairs yielded by another stream.
+///
+/// This is created by the `Stream::unzip` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct UnzipLeft<S, A, B>
+    where S: Stream<Item = (A, B)>,
+{
+    shared: Arc<Shared<S, Unzip<A, B>>>,
+}
+
+/// The stream of the second elements of the pairs yielded by another stream.
+///
+/// This is created by the `Stream::unzip` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct UnzipRight<S, A, B>
+    where S: Stream<Item = (A, B)>,
+{
+    shared: Arc<Shared<S, Unzip<A, B>>>,
+}
+
+struct Unzip<A, B>(PhantomData<fn((A, B))>);
+
+impl<A, B> Split<(A, B)> for Unzip<A, B> {
+    type Left = A;
+    type Right = B;
+
+    fn split(&mut self, (a, b): (A, B)) -> (Option<A>, Option<B>) {
+        (Some(a), Some(b))
+    }
+}
+
+pub fn new<S, A, B>(s: S, capacity: usize)
+                    -> (UnzipLeft<S, A, B>, UnzipRight<S, A, B>)
+    where S: Stream<Item = (A, B)>,
+{
+    let shared = split::new(s, capacity, Unzip(PhantomData));
+    (UnzipLeft { shared: shared.clone() }, UnzipRight { shared: shared })
+}
+
+impl<S, A, B> Stream for UnzipLeft<S, A, B>
+    where S: Stream<Item = (A, B)>,
+{
+    type Item = A;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<A>, S::Error> {
+        self.shared.poll_left()
+    }
+}
+
+impl<S, A, B> Stream for UnzipRight<S, A, B>
+    where S: Stream<Item = (A, B)>,
+{
+    type Item = B;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<B>, S::Error> {
+        self.shared.poll_right()
+    }
+}
+
+impl<S, A, B> Drop for UnzipLeft<S, A, B>
+    where S: Stream<Item = (A, B)>,
+{
+    fn drop(&mut self) {
+        self.shared.drop_left()
+    }
+}
+
+impl<S, A, B> Drop for UnzipRight<S, A, B>
+    where S: Stream<Item = (A, B)>,
+{
+    fn drop(&mut self) {
+        self.shared.drop_right()
+    }
+}
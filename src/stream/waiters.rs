@@ -0,0 +1,125 @@
+use std::prelude::v1::*;
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use task::{self, EventSet, Task, UnparkEvent};
+
+// The tasks waiting on a stream which is shared between several handles, such
+// as the sub-streams of `group_by` or the streams returned by `fork`, keyed by
+// an id for each handle.
+//
+// Whichever handle is polled pulls items from the shared stream, but the
+// stream itself only remembers the last task which polled it. The stream is
+// therefore polled through `poll`, which arranges for all the waiting tasks
+// to be unparked once it's ready, so that none of them is left waiting for an
+// item which another handle would have pulled.
+//
+// The handles poll the shared stream with a lock held, and unparking a task
+// may run it inline, so tasks aren't unparked directly. Instead they're
+// collected in a `Wakeups`, which unparks them once the lock is released.
+pub struct Waiters {
+    tasks: Mutex<HashMap<usize, Task>>,
+    // Whether the shared stream is being polled, and if so whether it has
+    // become ready meanwhile.
+    state: AtomicUsize,
+}
+
+const IDLE: usize = 0;
+const POLLING: usize = 1;
+const READY: usize = 2;
+
+// The tasks to unpark once the lock on a shared stream has been released.
+//
+// This is created before the lock is taken, so that it's dropped after the
+// lock. If it's dropped while panicking, for example because user code run
+// with the lock held panicked, all the waiting tasks are unparked so that
+// none of them waits forever on a stream which can't be polled any more.
+pub struct Wakeups<'a> {
+    waiters: &'a Waiters,
+    tasks: Vec<Task>,
+}
+
+impl Waiters {
+    pub fn new() -> Waiters {
+        Waiters {
+            tasks: Mutex::new(HashMap::new()),
+            state: AtomicUsize::new(IDLE),
+        }
+    }
+
+    pub fn wakeups(&self) -> Wakeups<'_> {
+        Wakeups {
+            waiters: self,
+            tasks: Vec::new(),
+        }
+    }
+
+    // Registers the current task as the one waiting on the handle `id`.
+    pub fn register(&self, id: usize) {
+        self.tasks().insert(id, task::park());
+    }
+
+    pub fn remove(&self, id: usize) {
+        self.tasks().remove(&id);
+    }
+
+    fn tasks(&self) -> MutexGuard<'_, HashMap<usize, Task>> {
+        // Nothing panics while this lock is held, but a waiter may be removed
+        // while unwinding, so poisoning is ignored rather than panicking again.
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl EventSet for Waiters {
+    fn insert(&self, _id: usize) {
+        // While the stream is being polled the lock is held, so the tasks are
+        // unparked by `poll` once the stream returns instead.
+        match self.state.compare_exchange(POLLING, READY, Ordering::SeqCst,
+                                          Ordering::SeqCst) {
+            Ok(_) | Err(READY) => {}
+            Err(_) => self.wakeups().wake_all(),
+        }
+    }
+}
+
+impl<'a> Wakeups<'a> {
+    pub fn wake(&mut self, id: usize) {
+        if let Some(task) = self.waiters.tasks().remove(&id) {
+            self.tasks.push(task);
+        }
+    }
+
+    pub fn wake_all(&mut self) {
+        let tasks = mem::take(&mut *self.waiters.tasks());
+        self.tasks.extend(tasks.into_values());
+    }
+}
+
+impl<'a> Drop for Wakeups<'a> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.wake_all();
+        }
+        for task in self.tasks.drain(..) {
+            task.unpark();
+        }
+    }
+}
+
+// Runs `f`, which polls the shared stream, such that all the tasks waiting on
+// `waiters` are unparked along with the current one when the stream is ready.
+pub fn poll<F, R>(waiters: &Arc<Waiters>, wakeups: &mut Wakeups, f: F) -> R
+    where F: FnOnce() -> R
+{
+    waiters.state.store(POLLING, Ordering::SeqCst);
+    let event = UnparkEvent::new(waiters.clone() as Arc<EventSet>, 0);
+    let ret = task::with_unpark_event(event, f);
+    if waiters.state.swap(IDLE, Ordering::SeqCst) == READY {
+        wakeups.wake_all();
+    }
+    ret
+}
@@ -0,0 +1,352 @@
+use std::prelude::v1::*;
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use {Future, IntoFuture, Poll, Async, CatchUnwind};
+use stream::Stream;
+use task::{self, Task};
+
+/// A future which runs a long-running future, restarting it whenever it fails.
+///
+/// This is created by the `supervise` function.
+#[must_use = "futures do nothing unless polled"]
+pub struct Supervisor<F, R> where R: IntoFuture {
+    factory: F,
+    policy: RestartPolicy,
+    current: Option<CatchUnwind<AssertUnwindSafe<R::Future>>>,
+    restarts: usize,
+    history: VecDeque<Instant>,
+    events: Option<Arc<Mutex<Queue<R::Error>>>>,
+}
+
+/// Describes how often a `Supervisor` may restart its child before giving up.
+///
+/// A policy allows at most `max_restarts` restarts within any window of time
+/// of length `within`. Once the child fails more often than that the
+/// supervisor gives up and resolves to the last failure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RestartPolicy {
+    max_restarts: usize,
+    within: Duration,
+}
+
+/// The error returned by a `Supervisor` when it has given up restarting its
+/// child.
+pub enum SupervisorError<E> {
+    /// The last attempt failed with the given error.
+    Failed(E),
+    /// The last attempt panicked with the given payload.
+    Panicked(Box<Any + Send>),
+}
+
+/// An event in the lifecycle of a child of a `Supervisor`.
+///
+/// Events are delivered through the stream returned by `Supervisor::events`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SupervisorEvent<E> {
+    /// The child was started. The payload is the number of times the child has
+    /// been restarted so far.
+    Started(usize),
+    /// The child failed with the given error and will be restarted.
+    Failed(E),
+    /// The child panicked with the given message and will be restarted.
+    Panicked(String),
+    /// The child completed successfully.
+    Completed,
+    /// The child failed and the restart policy doesn't allow another restart.
+    /// The failure is returned from the `Supervisor` itself.
+    GaveUp,
+}
+
+/// A stream of lifecycle events of a `Supervisor`.
+///
+/// This is created by the `Supervisor::events` method. The stream ends once
+/// the child completes, the supervisor gives up, or the supervisor is
+/// dropped.
+#[must_use = "streams do nothing unless polled"]
+pub struct SupervisorEvents<E> {
+    queue: Arc<Mutex<Queue<E>>>,
+}
+
+struct Queue<E> {
+    events: VecDeque<SupervisorEvent<E>>,
+    done: bool,
+    task: Option<Task>,
+}
+
+/// Creates a future which runs the futures created by `factory`, restarting
+/// them on failure according to `policy`.
+///
+/// The `factory` closure is called once to create the initial child future,
+/// and again each time the child fails with an error or panics. Panics are
+/// caught with the `catch_unwind` combinator. A panic in `factory` itself is
+/// treated like a panic of the child it was creating. Each supervisor follows a
+/// one-for-one strategy: it only ever restarts its own child, so a set of
+/// independent workers should each be given their own supervisor.
+///
+/// The returned future resolves to the child's value once it completes
+/// successfully. If the child fails more often than `policy` permits, the
+/// returned future resolves to an error containing the last failure.
+///
+/// Note that this function wraps each child, and each call to `factory`, in
+/// `AssertUnwindSafe` to catch their panics, so a child which panics must not
+/// leave state shared with its successor in an inconsistent state.
+///
+/// # Examples
+///
+/// ```
+/// use futures::*;
+/// use futures::stream::Stream;
+///
+/// let mut attempts = 0;
+/// let mut supervisor = supervise(RestartPolicy::new(5), move || {
+///     attempts += 1;
+///     if attempts < 3 {
+///         Err::<u32, u32>(attempts)
+///     } else {
+///         Ok(attempts)
+///     }
+/// });
+/// let events = supervisor.events();
+///
+/// assert_eq!(supervisor.wait().ok(), Some(3));
+/// assert_eq!(events.wait().map(|e| e.unwrap()).collect::<Vec<_>>(), vec![
+///     SupervisorEvent::Started(0),
+///     SupervisorEvent::Failed(1),
+///     SupervisorEvent::Started(1),
+///     SupervisorEvent::Failed(2),
+///     SupervisorEvent::Started(2),
+///     SupervisorEvent::Completed,
+/// ]);
+/// ```
+pub fn supervise<F, R>(policy: RestartPolicy, factory: F) -> Supervisor<F, R>
+    where F: FnMut() -> R,
+          R: IntoFuture,
+{
+    Supervisor {
+        factory: factory,
+        policy: policy,
+        current: None,
+        restarts: 0,
+        history: VecDeque::new(),
+        events: None,
+    }
+}
+
+impl RestartPolicy {
+    /// Creates a policy which allows at most `max_restarts` restarts within
+    /// any 60 second period.
+    pub fn new(max_restarts: usize) -> RestartPolicy {
+        RestartPolicy::within(max_restarts, Duration::from_secs(60))
+    }
+
+    /// Creates a policy which allows at most `max_restarts` restarts within
+    /// any period of the given length.
+    pub fn within(max_restarts: usize, period: Duration) -> RestartPolicy {
+        RestartPolicy {
+            max_restarts: max_restarts,
+            within: period,
+        }
+    }
+
+    /// Creates a policy which never restarts a child.
+    pub fn never() -> RestartPolicy {
+        RestartPolicy::new(0)
+    }
+
+    /// Creates a policy which always restarts a child, no matter how often it
+    /// fails.
+    pub fn always() -> RestartPolicy {
+        RestartPolicy::new(usize::MAX)
+    }
+}
+
+impl<F, R> Supervisor<F, R>
+    where F: FnMut() -> R,
+          R: IntoFuture,
+{
+    /// Returns a stream of the lifecycle events of the supervised child.
+    ///
+    /// Events are only recorded once this method has been called, and are
+    /// buffered until the returned stream consumes them. The stream may be
+    /// moved to another task, for example one which logs each event.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if it is called more than once.
+    pub fn events(&mut self) -> SupervisorEvents<R::Error> {
+        assert!(self.events.is_none(), "Supervisor::events called twice");
+        let queue = Arc::new(Mutex::new(Queue {
+            events: VecDeque::new(),
+            done: false,
+            task: None,
+        }));
+        self.events = Some(queue.clone());
+        SupervisorEvents { queue: queue }
+    }
+
+    /// Returns the number of times the child has been restarted.
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    fn emit(&self, event: SupervisorEvent<R::Error>) {
+        if let Some(ref queue) = self.events {
+            let mut queue = queue.lock().unwrap();
+            queue.events.push_back(event);
+            let task = queue.task.take();
+            drop(queue);
+            if let Some(task) = task {
+                task.unpark();
+            }
+        }
+    }
+
+    // Records a failure, returning whether the policy permits a restart.
+    fn may_restart(&mut self) -> bool {
+        if self.policy.max_restarts == usize::MAX {
+            self.restarts += 1;
+            return true
+        }
+        let now = Instant::now();
+        let within = self.policy.within;
+        let expired = |t: &Instant| now.duration_since(*t) >= within;
+        while self.history.front().is_some_and(expired) {
+            self.history.pop_front();
+        }
+        if self.history.len() >= self.policy.max_restarts {
+            return false
+        }
+        self.history.push_back(now);
+        self.restarts += 1;
+        true
+    }
+
+    // Creates a new child, returning the payload of a panic in the factory.
+    fn start(&mut self) -> Result<(), Box<Any + Send>> {
+        let factory = &mut self.factory;
+        let future = try!(panic::catch_unwind(AssertUnwindSafe(|| {
+            factory().into_future()
+        })));
+        self.current = Some(AssertUnwindSafe(future).catch_unwind());
+        self.emit(SupervisorEvent::Started(self.restarts));
+        Ok(())
+    }
+
+    // Handles a failure of the child, restarting it if the policy permits.
+    fn fail(&mut self, err: SupervisorError<R::Error>)
+            -> Poll<R::Item, SupervisorError<R::Error>> {
+        if !self.may_restart() {
+            self.emit(SupervisorEvent::GaveUp);
+            return Err(err)
+        }
+        match err {
+            SupervisorError::Failed(e) => {
+                self.emit(SupervisorEvent::Failed(e));
+            }
+            SupervisorError::Panicked(payload) => {
+                self.emit(SupervisorEvent::Panicked(panic_message(&payload)));
+            }
+        }
+
+        // The restart happens the next time we're polled, rather than in a
+        // loop here, so a child which fails immediately doesn't starve other
+        // work on this thread.
+        task::park().unpark();
+        Ok(Async::NotReady)
+    }
+}
+
+impl<F, R> Future for Supervisor<F, R>
+    where F: FnMut() -> R,
+          R: IntoFuture,
+{
+    type Item = R::Item;
+    type Error = SupervisorError<R::Error>;
+
+    fn poll(&mut self) -> Poll<R::Item, SupervisorError<R::Error>> {
+        if self.current.is_none() {
+            if let Err(payload) = self.start() {
+                return self.fail(SupervisorError::Panicked(payload))
+            }
+        }
+
+        let res = match self.current.as_mut().unwrap().poll() {
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(res)) => res.map_err(SupervisorError::Failed),
+            Err(payload) => Err(SupervisorError::Panicked(payload)),
+        };
+        self.current = None;
+
+        match res {
+            Ok(item) => {
+                self.emit(SupervisorEvent::Completed);
+                Ok(Async::Ready(item))
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+}
+
+impl<F, R> Drop for Supervisor<F, R> where R: IntoFuture {
+    fn drop(&mut self) {
+        if let Some(ref queue) = self.events {
+            let mut queue = queue.lock().unwrap();
+            queue.done = true;
+            let task = queue.task.take();
+            drop(queue);
+            if let Some(task) = task {
+                task.unpark();
+            }
+        }
+    }
+}
+
+fn panic_message(payload: &Box<Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&'static str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<Any>".to_string()
+    }
+}
+
+impl<E> Stream for SupervisorEvents<E> {
+    type Item = SupervisorEvent<E>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<SupervisorEvent<E>>, ()> {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(event) = queue.events.pop_front() {
+            if matches!(event, SupervisorEvent::Completed |
+                               SupervisorEvent::GaveUp) {
+                queue.done = true;
+            }
+            return Ok(Async::Ready(Some(event)))
+        }
+        if queue.done {
+            return Ok(Async::Ready(None))
+        }
+        queue.task = Some(task::park());
+        Ok(Async::NotReady)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for SupervisorError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SupervisorError::Failed(ref e) => {
+                f.debug_tuple("Failed").field(e).finish()
+            }
+            SupervisorError::Panicked(ref payload) => {
+                f.debug_tuple("Panicked").field(&panic_message(payload)).finish()
+            }
+        }
+    }
+}
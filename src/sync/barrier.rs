@@ -0,0 +1,163 @@
+use std::prelude::v1::*;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use {Future, Poll, Async};
+use task::{self, Task};
+
+/// A barrier which lets a fixed number of tasks wait for each other to reach
+/// the same point.
+///
+/// Each task calls `wait` and polls the returned future, which resolves once
+/// `n` tasks are waiting at once. Exactly one of these, the last one to
+/// arrive, is designated the leader, which can for example be used to run a
+/// step which should only happen once per phase. The barrier is then reset and
+/// can be used again for the next phase.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use futures::Future;
+/// use futures::sync::Barrier;
+///
+/// let barrier = Barrier::new(3);
+/// let workers = (0..3).map(|_| {
+///     let barrier = barrier.clone();
+///     thread::spawn(move || {
+///         // ... load a shard ...
+///         barrier.wait().wait().unwrap().is_leader()
+///     })
+/// }).collect::<Vec<_>>();
+///
+/// let leaders = workers.into_iter()
+///                      .map(|w| w.join().unwrap())
+///                      .filter(|&leader| leader)
+///                      .count();
+/// assert_eq!(leaders, 1);
+/// ```
+#[derive(Clone)]
+pub struct Barrier {
+    inner: Arc<Mutex<State>>,
+}
+
+/// A future which resolves once all tasks have arrived at a `Barrier`.
+///
+/// This is created by the `Barrier::wait` method. A task only counts as
+/// having arrived at the barrier once this future is first polled. Dropping
+/// the future before it resolves withdraws the task from the barrier again.
+#[must_use = "futures do nothing unless polled"]
+pub struct BarrierWait {
+    inner: Arc<Mutex<State>>,
+    state: WaitState,
+}
+
+/// The value a `BarrierWait` future resolves to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BarrierWaitResult {
+    leader: bool,
+}
+
+enum WaitState {
+    Init,
+    Waiting(usize, usize),
+    Done,
+}
+
+struct State {
+    n: usize,
+    generation: usize,
+    next_id: usize,
+    // Tasks which have arrived in the current generation, keyed by an id
+    // unique to each `BarrierWait`.
+    waiting: HashMap<usize, Task>,
+}
+
+impl Barrier {
+    /// Creates a new barrier which releases tasks in groups of `n`.
+    ///
+    /// A barrier created with an `n` of 0 behaves like one with an `n` of 1,
+    /// in that every task passes through immediately as a leader.
+    pub fn new(n: usize) -> Barrier {
+        Barrier {
+            inner: Arc::new(Mutex::new(State {
+                n: n,
+                generation: 0,
+                next_id: 0,
+                waiting: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Returns a future which resolves once `n` tasks are waiting on this
+    /// barrier.
+    ///
+    /// See `BarrierWait` for details.
+    pub fn wait(&self) -> BarrierWait {
+        BarrierWait {
+            inner: self.inner.clone(),
+            state: WaitState::Init,
+        }
+    }
+}
+
+impl Future for BarrierWait {
+    type Item = BarrierWaitResult;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<BarrierWaitResult, ()> {
+        let tasks = {
+            let mut state = self.inner.lock().unwrap();
+            match self.state {
+                WaitState::Init => {
+                    if state.waiting.len() + 1 < state.n {
+                        let id = state.next_id;
+                        state.next_id += 1;
+                        state.waiting.insert(id, task::park());
+                        self.state = WaitState::Waiting(state.generation, id);
+                        return Ok(Async::NotReady)
+                    }
+
+                    // We're the last to arrive, so release everyone else and
+                    // start the next generation.
+                    state.generation = state.generation.wrapping_add(1);
+                    state.waiting.drain().map(|(_, task)| task).collect::<Vec<_>>()
+                }
+                WaitState::Waiting(generation, id) => {
+                    if generation == state.generation {
+                        state.waiting.insert(id, task::park());
+                        return Ok(Async::NotReady)
+                    }
+                    self.state = WaitState::Done;
+                    return Ok(Async::Ready(BarrierWaitResult { leader: false }))
+                }
+                WaitState::Done => panic!("cannot poll BarrierWait twice"),
+            }
+        };
+        for task in tasks {
+            task.unpark();
+        }
+        self.state = WaitState::Done;
+        Ok(Async::Ready(BarrierWaitResult { leader: true }))
+    }
+}
+
+impl Drop for BarrierWait {
+    fn drop(&mut self) {
+        if let WaitState::Waiting(generation, id) = self.state {
+            let mut state = self.inner.lock().unwrap();
+            if generation == state.generation {
+                state.waiting.remove(&id);
+            }
+        }
+    }
+}
+
+impl BarrierWaitResult {
+    /// Returns whether this task is the leader of its group, which is true
+    /// for exactly one of the tasks released together.
+    pub fn is_leader(&self) -> bool {
+        self.leader
+    }
+}
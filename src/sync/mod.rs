@@ -0,0 +1,15 @@
+//! Synchronization primitives for coordinating tasks
+//!
+//! The types in this module are the asynchronous counterparts of the
+//! primitives in `std::sync`: rather than blocking the current thread, waiting
+//! on them is represented by a future which resolves once the condition being
+//! waited for has happened. `Notify` and `Barrier` are cheap handles which can
+//! be cloned and sent to other threads, with all clones referring to the same
+//! primitive.
+
+mod barrier;
+mod notify;
+mod once_cell;
+pub use self::barrier::{Barrier, BarrierWait, BarrierWaitResult};
+pub use self::notify::{Notify, Notified};
+pub use self::once_cell::{OnceCell, GetOrInit};
@@ -0,0 +1,207 @@
+use std::prelude::v1::*;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use {Future, Poll, Async};
+use task::{self, Task};
+
+/// A notification which tasks can wait for, similar to a condition variable.
+///
+/// Tasks wait to be notified through the `Notified` future returned by
+/// `notified`, and are woken up by `notify_one`, which wakes a single waiter,
+/// or `notify_all`, which wakes every task currently waiting.
+///
+/// If `notify_one` is called while no task is waiting, the notification isn't
+/// lost but is instead stored as a *permit*. A later `Notified` future
+/// consumes a permit, if there is one, and then resolves immediately. Permits
+/// accumulate, so each call to `notify_one` lets exactly one `Notified` future
+/// resolve. `notify_all` only affects the tasks which are waiting at the time
+/// and doesn't store permits.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use futures::Future;
+/// use futures::sync::Notify;
+///
+/// let notify = Notify::new();
+/// let notify2 = notify.clone();
+///
+/// let worker = thread::spawn(move || {
+///     // ... load some data ...
+///     notify2.notify_one();
+/// });
+///
+/// notify.notified().wait().unwrap();
+/// worker.join().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Notify {
+    inner: Arc<Mutex<State>>,
+}
+
+/// A future which resolves once the `Notify` it was created from is notified.
+///
+/// This is created by the `Notify::notified` method. The future only starts
+/// waiting when it's first polled, so notifications sent by `notify_all`
+/// before that point aren't observed.
+///
+/// If the future is dropped after being woken up by `notify_one` but before
+/// observing the notification, the notification is passed on to the next
+/// waiting task, or stored as a permit, so that it isn't lost.
+#[must_use = "futures do nothing unless polled"]
+pub struct Notified {
+    inner: Arc<Mutex<State>>,
+    state: WaitState,
+}
+
+enum WaitState {
+    Init,
+    Waiting(usize),
+    Done,
+}
+
+struct State {
+    permits: usize,
+    next_id: usize,
+    // Tasks which are waiting to be notified, oldest first.
+    waiting: VecDeque<(usize, Task)>,
+    // Waiters which have been notified but haven't observed it yet, along
+    // with whether the notification came from `notify_one`.
+    notified: HashMap<usize, bool>,
+}
+
+impl Notify {
+    /// Creates a new `Notify` with no permits and no waiting tasks.
+    pub fn new() -> Notify {
+        Notify {
+            inner: Arc::new(Mutex::new(State {
+                permits: 0,
+                next_id: 0,
+                waiting: VecDeque::new(),
+                notified: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Returns a future which resolves once this `Notify` is notified.
+    ///
+    /// See `Notified` for details.
+    pub fn notified(&self) -> Notified {
+        Notified {
+            inner: self.inner.clone(),
+            state: WaitState::Init,
+        }
+    }
+
+    /// Wakes up the task which has been waiting the longest, or stores a
+    /// permit if no task is waiting.
+    pub fn notify_one(&self) {
+        let task = self.inner.lock().unwrap().notify_one();
+        if let Some(task) = task {
+            task.unpark();
+        }
+    }
+
+    /// Wakes up every task which is currently waiting.
+    ///
+    /// Unlike `notify_one`, this doesn't store a permit if no task is
+    /// waiting.
+    pub fn notify_all(&self) {
+        let tasks = {
+            let mut state = self.inner.lock().unwrap();
+            let waiting = state.waiting.drain(..).collect::<Vec<_>>();
+            waiting.into_iter().map(|(id, task)| {
+                state.notified.insert(id, false);
+                task
+            }).collect::<Vec<_>>()
+        };
+        for task in tasks {
+            task.unpark();
+        }
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Notify {
+        Notify::new()
+    }
+}
+
+impl State {
+    // Notifies the oldest waiter, returning its task to be unparked once the
+    // lock has been released.
+    fn notify_one(&mut self) -> Option<Task> {
+        match self.waiting.pop_front() {
+            Some((id, task)) => {
+                self.notified.insert(id, true);
+                Some(task)
+            }
+            None => {
+                self.permits += 1;
+                None
+            }
+        }
+    }
+}
+
+impl Future for Notified {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        let mut state = self.inner.lock().unwrap();
+        match self.state {
+            WaitState::Init => {
+                if state.permits > 0 {
+                    state.permits -= 1;
+                    self.state = WaitState::Done;
+                    return Ok(Async::Ready(()))
+                }
+                let id = state.next_id;
+                state.next_id += 1;
+                state.waiting.push_back((id, task::park()));
+                self.state = WaitState::Waiting(id);
+                Ok(Async::NotReady)
+            }
+            WaitState::Waiting(id) => {
+                if state.notified.remove(&id).is_some() {
+                    self.state = WaitState::Done;
+                    return Ok(Async::Ready(()))
+                }
+                for waiter in state.waiting.iter_mut() {
+                    if waiter.0 == id {
+                        waiter.1 = task::park();
+                    }
+                }
+                Ok(Async::NotReady)
+            }
+            WaitState::Done => panic!("cannot poll Notified twice"),
+        }
+    }
+}
+
+impl Drop for Notified {
+    fn drop(&mut self) {
+        let id = match self.state {
+            WaitState::Waiting(id) => id,
+            _ => return,
+        };
+        let task = {
+            let mut state = self.inner.lock().unwrap();
+            match state.notified.remove(&id) {
+                Some(true) => state.notify_one(),
+                Some(false) => None,
+                None => {
+                    state.waiting.retain(|w| w.0 != id);
+                    None
+                }
+            }
+        };
+        if let Some(task) = task {
+            task.unpark();
+        }
+    }
+}
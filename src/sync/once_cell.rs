@@ -0,0 +1,221 @@
+use std::prelude::v1::*;
+
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use {Future, IntoFuture, Poll, Async};
+use task::{self, Task};
+
+/// A thread-safe cell which is initialized at most once, by a future.
+///
+/// The value of the cell is produced by the first task to call `get_or_init`
+/// on an empty cell, which runs its initializer future to completion. Other
+/// tasks calling `get_or_init` in the meantime don't run their initializers
+/// but wait for the value instead. If the initializer fails, its error is
+/// returned to the task which ran it and one of the waiting tasks takes over,
+/// running its own initializer, so the cell is initialized as soon as any
+/// initializer succeeds. The same happens if the initializer panics, or if its
+/// future is dropped before completing.
+///
+/// Unlike `lazy`, which also defers the creation of a future, a `OnceCell` is
+/// meant to be shared, typically through an `Arc` or a `static`, and hands out
+/// references to its value.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use futures::{Future, finished};
+/// use futures::sync::OnceCell;
+///
+/// let config = Arc::new(OnceCell::new());
+/// let threads = (0..4).map(|_| {
+///     let config = config.clone();
+///     thread::spawn(move || {
+///         // Only one of these initializers is run.
+///         let value = config.get_or_init(|| finished::<u32, ()>(42))
+///                           .wait()
+///                           .unwrap();
+///         assert_eq!(*value, 42);
+///     })
+/// }).collect::<Vec<_>>();
+/// for t in threads {
+///     t.join().unwrap();
+/// }
+/// assert_eq!(config.get(), Some(&42));
+/// ```
+pub struct OnceCell<T> {
+    ready: AtomicBool,
+    value: UnsafeCell<Option<T>>,
+    state: Mutex<State>,
+}
+
+// The value is only written by the one task initializing the cell, before
+// `ready` is set, and only read after `ready` is set, so references to it can
+// be handed out to any thread, like those to the contents of an `RwLock`.
+unsafe impl<T: Send> Send for OnceCell<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceCell<T> {}
+
+/// A future which resolves to the value of a `OnceCell`, initializing it if
+/// needed.
+///
+/// This is created by the `OnceCell::get_or_init` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct GetOrInit<'a, T: 'a, F, U> where U: IntoFuture<Item = T> {
+    cell: &'a OnceCell<T>,
+    init: Option<F>,
+    state: InitState<U::Future>,
+}
+
+enum InitState<F> {
+    Start,
+    Waiting(usize),
+    // The initializer is being called, which may panic.
+    Starting,
+    Running(F),
+}
+
+struct State {
+    initializing: bool,
+    next_id: usize,
+    // Tasks waiting for another task to initialize the cell.
+    waiting: HashMap<usize, Task>,
+}
+
+impl<T> OnceCell<T> {
+    /// Creates a new, empty cell.
+    pub fn new() -> OnceCell<T> {
+        OnceCell {
+            ready: AtomicBool::new(false),
+            value: UnsafeCell::new(None),
+            state: Mutex::new(State {
+                initializing: false,
+                next_id: 0,
+                waiting: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Returns the value of the cell, or `None` if it hasn't been initialized
+    /// yet.
+    pub fn get(&self) -> Option<&T> {
+        if self.ready.load(Ordering::SeqCst) {
+            unsafe { (*self.value.get()).as_ref() }
+        } else {
+            None
+        }
+    }
+
+    /// Returns a future which resolves to the value of the cell, initializing
+    /// it with the future returned by `f` if it's empty.
+    ///
+    /// The closure `f` is only called if this task ends up initializing the
+    /// cell, see `OnceCell` for details. The returned future fails with the
+    /// error of the initializer if it was run and failed.
+    pub fn get_or_init<F, U>(&self, f: F) -> GetOrInit<'_, T, F, U>
+        where F: FnOnce() -> U,
+              U: IntoFuture<Item = T>,
+    {
+        GetOrInit {
+            cell: self,
+            init: Some(f),
+            state: InitState::Start,
+        }
+    }
+
+    // Stops initializing the cell, returning the tasks which were waiting for
+    // it so they can be unparked once the lock has been released.
+    fn finish(&self, value: Option<T>) -> Vec<Task> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(value) = value {
+            unsafe { *self.value.get() = Some(value); }
+            self.ready.store(true, Ordering::SeqCst);
+        }
+        state.initializing = false;
+        state.waiting.drain().map(|(_, task)| task).collect()
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> OnceCell<T> {
+        OnceCell::new()
+    }
+}
+
+impl<'a, T, F, U> Future for GetOrInit<'a, T, F, U>
+    where F: FnOnce() -> U,
+          U: IntoFuture<Item = T>,
+{
+    type Item = &'a T;
+    type Error = U::Error;
+
+    fn poll(&mut self) -> Poll<&'a T, U::Error> {
+        let cell = self.cell;
+        if let InitState::Running(ref mut future) = self.state {
+            let (value, result) = match future.poll() {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(value)) => (Some(value), Ok(())),
+                Err(e) => (None, Err(e)),
+            };
+            self.state = InitState::Start;
+            for task in cell.finish(value) {
+                task.unpark();
+            }
+            try!(result);
+            return Ok(Async::Ready(cell.get().unwrap()))
+        }
+
+        if let Some(value) = cell.get() {
+            return Ok(Async::Ready(value))
+        }
+        {
+            let mut state = cell.state.lock().unwrap();
+            if let InitState::Waiting(id) = self.state {
+                state.waiting.remove(&id);
+            }
+            // The cell may have been initialized since we last looked.
+            if let Some(value) = cell.get() {
+                return Ok(Async::Ready(value))
+            }
+            if state.initializing {
+                let id = state.next_id;
+                state.next_id += 1;
+                state.waiting.insert(id, task::park());
+                self.state = InitState::Waiting(id);
+                return Ok(Async::NotReady)
+            }
+            state.initializing = true;
+        }
+
+        // If the initializer panics from here on the cell is released when
+        // this future is dropped, so the waiting tasks aren't stuck.
+        self.state = InitState::Starting;
+        let init = self.init.take().expect("cannot poll GetOrInit twice");
+        let future = init().into_future();
+        self.state = InitState::Running(future);
+        self.poll()
+    }
+}
+
+impl<'a, T, F, U> Drop for GetOrInit<'a, T, F, U>
+    where U: IntoFuture<Item = T>,
+{
+    fn drop(&mut self) {
+        match self.state {
+            InitState::Start => {}
+            InitState::Waiting(id) => {
+                self.cell.state.lock().unwrap().waiting.remove(&id);
+            }
+            // Let one of the waiting tasks take over the initialization.
+            InitState::Starting |
+            InitState::Running(_) => {
+                for task in self.cell.finish(None) {
+                    task.unpark();
+                }
+            }
+        }
+    }
+}
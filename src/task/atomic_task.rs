@@ -0,0 +1,179 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, SeqCst};
+
+use task::{self, Task};
+
+/// A place to store the task waiting on an event, which may be notified from
+/// another thread.
+///
+/// Futures which wait for something to happen elsewhere typically store the
+/// `Task` returned by `task::park` in a location shared with whoever will
+/// eventually signal the event. Doing this with a lock is easy to get wrong:
+/// a notification which arrives while the task is being stored can be lost,
+/// leaving the future waiting forever. `AtomicTask` implements this pattern
+/// without locking.
+///
+/// The waiting future calls `register` each time it's about to return
+/// `NotReady`, and the other side calls `notify` once the event has happened.
+/// After registering, the future must check for the event once more before
+/// returning `NotReady`, as it may have happened just before the task was
+/// registered.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use futures::{Future, Poll, Async};
+/// use futures::task::AtomicTask;
+///
+/// struct Flag {
+///     set: AtomicBool,
+///     task: AtomicTask,
+/// }
+///
+/// struct WaitForFlag(Arc<Flag>);
+///
+/// impl Future for WaitForFlag {
+///     type Item = ();
+///     type Error = ();
+///
+///     fn poll(&mut self) -> Poll<(), ()> {
+///         if self.0.set.load(Ordering::SeqCst) {
+///             return Ok(Async::Ready(()))
+///         }
+///         self.0.task.register();
+///         if self.0.set.load(Ordering::SeqCst) {
+///             Ok(Async::Ready(()))
+///         } else {
+///             Ok(Async::NotReady)
+///         }
+///     }
+/// }
+///
+/// fn set_flag(flag: &Flag) {
+///     flag.set.store(true, Ordering::SeqCst);
+///     flag.task.notify();
+/// }
+/// # let flag = Arc::new(Flag {
+/// #     set: AtomicBool::new(false),
+/// #     task: AtomicTask::new(),
+/// # });
+/// # set_flag(&flag);
+/// # WaitForFlag(flag).wait().unwrap();
+/// ```
+pub struct AtomicTask {
+    state: AtomicUsize,
+    task: UnsafeCell<Option<Task>>,
+}
+
+// The stored task is only ever accessed by the one thread which managed to
+// move `state` out of `WAITING`, so this is just like a `Mutex<Option<Task>>`.
+unsafe impl Send for AtomicTask {}
+unsafe impl Sync for AtomicTask {}
+
+// There are three possible states, any combination of the latter two flags
+// being possible at once:
+
+// Nobody is accessing the stored task.
+const WAITING: usize = 0;
+
+// A task is being stored by `register`.
+const REGISTERING: usize = 1;
+
+// A call to `notify` is taking the stored task out to unpark it, or arrived
+// while a task was being stored, in which case `register` unparks the task
+// it stored itself.
+const NOTIFYING: usize = 2;
+
+impl AtomicTask {
+    /// Creates a new `AtomicTask` with no task registered.
+    pub fn new() -> AtomicTask {
+        AtomicTask {
+            state: AtomicUsize::new(WAITING),
+            task: UnsafeCell::new(None),
+        }
+    }
+
+    /// Registers the current task to be unparked by the next call to
+    /// `notify`, replacing any previously registered task.
+    ///
+    /// If this races with a call to `notify`, or with a call to `register`
+    /// on another thread, the current task is unparked immediately so that it
+    /// polls again rather than missing the notification.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if called outside the context of a task, as
+    /// it calls `task::park`.
+    pub fn register(&self) {
+        let task = task::park();
+        let res = self.state.compare_exchange(WAITING, REGISTERING,
+                                              AcqRel, Acquire);
+        match res {
+            Ok(_) => {
+                unsafe { *self.task.get() = Some(task); }
+
+                // If `notify` was called while we were storing the task it
+                // has left the task for us to unpark, which we do after
+                // releasing the slot.
+                let res = self.state.compare_exchange(REGISTERING, WAITING,
+                                                      AcqRel, Acquire);
+                if let Err(old) = res {
+                    debug_assert_eq!(old, REGISTERING | NOTIFYING);
+                    let task = unsafe { (*self.task.get()).take() };
+                    self.state.store(WAITING, SeqCst);
+                    if let Some(task) = task {
+                        task.unpark();
+                    }
+                }
+            }
+            Err(_) => task.unpark(),
+        }
+    }
+
+    /// Unparks the registered task, if any, removing it from this
+    /// `AtomicTask`.
+    ///
+    /// This may be called from any thread, concurrently with `register` and
+    /// with other calls to `notify`.
+    pub fn notify(&self) {
+        if self.state.fetch_or(NOTIFYING, SeqCst) == WAITING {
+            let task = unsafe { (*self.task.get()).take() };
+            self.state.fetch_and(!NOTIFYING, SeqCst);
+            if let Some(task) = task {
+                task.unpark();
+            }
+        }
+    }
+
+    /// Removes the registered task, if any, without unparking it.
+    ///
+    /// This is used by a future which no longer needs to be notified, for
+    /// example because it's being dropped. If this races with `notify`, the
+    /// task may be unparked anyway, and `None` is returned.
+    pub fn take(&self) -> Option<Task> {
+        let res = self.state.compare_exchange(WAITING, NOTIFYING,
+                                              AcqRel, Acquire);
+        if res.is_err() {
+            return None
+        }
+        let task = unsafe { (*self.task.get()).take() };
+        self.state.fetch_and(!NOTIFYING, SeqCst);
+        task
+    }
+}
+
+impl Default for AtomicTask {
+    fn default() -> AtomicTask {
+        AtomicTask::new()
+    }
+}
+
+impl fmt::Debug for AtomicTask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AtomicTask").finish()
+    }
+}
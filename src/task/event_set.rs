@@ -0,0 +1,298 @@
+use std::prelude::v1::*;
+
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use task::EventSet;
+use task::segments::{self, Segments};
+
+const BITS: usize = mem::size_of::<usize>() * 8;
+
+/// A lock-free `EventSet` for a fixed range of ids.
+///
+/// This set can hold ids in the range `0..capacity`, using one bit per id, and
+/// is suited to tracking wakeups of a fixed number of children. Inserting an
+/// id which is already present does nothing.
+///
+/// # Examples
+///
+/// ```
+/// use futures::task::{AtomicBitSet, EventSet};
+///
+/// let set = AtomicBitSet::new(100);
+/// set.insert(70);
+/// set.insert(3);
+/// set.insert(70);
+/// assert_eq!(set.drain().collect::<Vec<_>>(), vec![3, 70]);
+/// assert_eq!(set.drain().count(), 0);
+/// ```
+pub struct AtomicBitSet {
+    words: Box<[AtomicUsize]>,
+    capacity: usize,
+}
+
+/// An `EventSet` for ids of any size, growing as needed.
+///
+/// Like `AtomicBitSet` this set uses one bit per id, but it isn't limited to
+/// a fixed range. The set grows in segments which are never moved once
+/// allocated, so inserting an id is lock-free even when the set needs to grow
+/// to accommodate it, and may happen while the set is being drained.
+#[derive(Default)]
+pub struct GrowableBitSet {
+    words: Segments<AtomicUsize>,
+}
+
+/// An `EventSet` which records ids in the order they were inserted.
+///
+/// This is a lock-free multi-producer, single-consumer queue: any number of
+/// threads may insert ids while the task owning the queue drains it. Unlike
+/// the bit sets an id inserted multiple times is also drained multiple times,
+/// once for each insertion.
+///
+/// # Examples
+///
+/// ```
+/// use futures::task::{ReadyQueue, EventSet};
+///
+/// let queue = ReadyQueue::new();
+/// queue.insert(5);
+/// queue.insert(1);
+/// queue.insert(5);
+/// assert_eq!(queue.drain().collect::<Vec<_>>(), vec![5, 1, 5]);
+/// ```
+#[derive(Default)]
+pub struct ReadyQueue {
+    // A stack of inserted ids, most recent first.
+    head: AtomicPtr<Node>,
+}
+
+struct Node {
+    id: usize,
+    next: *mut Node,
+}
+
+/// An iterator over the ids drained from an `AtomicBitSet`, in increasing
+/// order.
+pub struct Drain<'a> {
+    words: &'a [AtomicUsize],
+    bits: Bits,
+}
+
+/// An iterator over the ids drained from a `GrowableBitSet`, in increasing
+/// order.
+pub struct GrowableDrain<'a> {
+    set: &'a GrowableBitSet,
+    // The segment of words being drained.
+    segment: usize,
+    bits: Bits,
+}
+
+/// An iterator over the ids drained from a `ReadyQueue`, in the order they
+/// were inserted.
+pub struct ReadyDrain {
+    // The drained ids, least recent first.
+    head: *mut Node,
+}
+
+// State of a walk over a slice of words, clearing each word as it's reached.
+struct Bits {
+    index: usize,
+    current: usize,
+}
+
+impl Bits {
+    fn new() -> Bits {
+        Bits { index: 0, current: 0 }
+    }
+
+    fn next(&mut self, words: &[AtomicUsize]) -> Option<usize> {
+        while self.current == 0 {
+            if self.index >= words.len() {
+                return None
+            }
+            self.current = words[self.index].swap(0, Ordering::SeqCst);
+            self.index += 1;
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some((self.index - 1) * BITS + bit)
+    }
+}
+
+fn set_bit(words: &[AtomicUsize], id: usize) {
+    words[id / BITS].fetch_or(1 << (id % BITS), Ordering::SeqCst);
+}
+
+impl AtomicBitSet {
+    /// Creates a new, empty set which can hold ids in the range
+    /// `0..capacity`.
+    pub fn new(capacity: usize) -> AtomicBitSet {
+        let words = (0..capacity.div_ceil(BITS))
+                        .map(|_| AtomicUsize::new(0))
+                        .collect::<Vec<_>>();
+        AtomicBitSet {
+            words: words.into_boxed_slice(),
+            capacity: capacity,
+        }
+    }
+
+    /// Returns the number of ids this set can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Removes all ids from the set, returning an iterator over them.
+    ///
+    /// Ids are removed as the iterator advances, so ids inserted concurrently
+    /// may or may not be returned. Any id which isn't returned remains in the
+    /// set.
+    pub fn drain(&self) -> Drain<'_> {
+        Drain {
+            words: &self.words,
+            bits: Bits::new(),
+        }
+    }
+}
+
+impl EventSet for AtomicBitSet {
+    /// Inserts `id` into the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than the capacity of the set.
+    fn insert(&self, id: usize) {
+        assert!(id < self.capacity, "id {} out of range for set of capacity {}",
+                id, self.capacity);
+        set_bit(&self.words, id);
+    }
+}
+
+impl<'a> Iterator for Drain<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.bits.next(self.words)
+    }
+}
+
+impl GrowableBitSet {
+    /// Creates a new, empty set.
+    pub fn new() -> GrowableBitSet {
+        GrowableBitSet { words: Segments::new() }
+    }
+
+    /// Removes all ids from the set, returning an iterator over them.
+    ///
+    /// See `AtomicBitSet::drain` for details.
+    pub fn drain(&self) -> GrowableDrain<'_> {
+        GrowableDrain {
+            set: self,
+            segment: 0,
+            bits: Bits::new(),
+        }
+    }
+}
+
+impl EventSet for GrowableBitSet {
+    /// Inserts `id` into the set, growing it if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set would need more than `u32::MAX` words of bits to
+    /// hold `id`.
+    fn insert(&self, id: usize) {
+        self.words.get(id / BITS).fetch_or(1 << (id % BITS), Ordering::SeqCst);
+    }
+}
+
+impl<'a> Iterator for GrowableDrain<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        // Segment `n` holds the words from index `2^n - 1` on, and segments
+        // which haven't been allocated have no ids in them.
+        while self.segment < segments::SEGMENTS {
+            if let Some(words) = self.set.words.segment(self.segment) {
+                if let Some(id) = self.bits.next(words) {
+                    return Some(((1 << self.segment) - 1) * BITS + id)
+                }
+            }
+            self.segment += 1;
+            self.bits = Bits::new();
+        }
+        None
+    }
+}
+
+impl ReadyQueue {
+    /// Creates a new, empty queue.
+    pub fn new() -> ReadyQueue {
+        ReadyQueue { head: AtomicPtr::new(ptr::null_mut()) }
+    }
+
+    /// Removes all ids from the queue, returning an iterator over them in the
+    /// order they were inserted.
+    pub fn drain(&self) -> ReadyDrain {
+        // Take the whole stack at once and reverse it, so the least recently
+        // inserted id comes first.
+        let mut cur = self.head.swap(ptr::null_mut(), Ordering::SeqCst);
+        let mut reversed = ptr::null_mut();
+        while !cur.is_null() {
+            unsafe {
+                let next = (*cur).next;
+                (*cur).next = reversed;
+                reversed = cur;
+                cur = next;
+            }
+        }
+        ReadyDrain { head: reversed }
+    }
+}
+
+impl EventSet for ReadyQueue {
+    fn insert(&self, id: usize) {
+        let node = Box::into_raw(Box::new(Node {
+            id: id,
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Ordering::SeqCst);
+        loop {
+            unsafe { (*node).next = head; }
+            match self.head.compare_exchange(head, node,
+                                             Ordering::SeqCst,
+                                             Ordering::SeqCst) {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+}
+
+impl Drop for ReadyQueue {
+    fn drop(&mut self) {
+        drop(self.drain());
+    }
+}
+
+impl Iterator for ReadyDrain {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.head.is_null() {
+            return None
+        }
+        let node = unsafe { Box::from_raw(self.head) };
+        self.head = node.next;
+        Some(node.id)
+    }
+}
+
+impl Drop for ReadyDrain {
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+    }
+}
+
+// The nodes referenced by a drain are owned exclusively by it.
+unsafe impl Send for ReadyDrain {}
@@ -0,0 +1,300 @@
+use std::prelude::v1::*;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use {Future, Poll, Async};
+use task::{self, Task, Executor};
+
+/// A future representing the completion of every task spawned into a group.
+///
+/// This is created by the `task::group` function. The future resolves once all
+/// children of the group have finished and every `GroupHandle` has been
+/// dropped (so no more children can be added). If any child fails, the first
+/// error is returned from this future and all other children are canceled.
+///
+/// Dropping this future will abort every child of the group. Children notice
+/// the cancellation the next time they're polled and drop their underlying
+/// future at that point.
+#[must_use = "futures do nothing unless polled"]
+pub struct TaskGroup<E> {
+    inner: Arc<Inner<E>>,
+}
+
+/// A handle used to add children to a group of tasks.
+///
+/// Handles can be cloned and sent to other threads. While any handle is alive
+/// the corresponding `TaskGroup` future will not resolve successfully, as more
+/// children could still be added.
+pub struct GroupHandle<E> {
+    inner: Arc<Inner<E>>,
+}
+
+/// A child future of a group of tasks, created by `GroupHandle::wrap`.
+///
+/// This future is intended to be run on an executor, for example through
+/// `Spawn::execute`. It resolves successfully once the underlying future has
+/// completed or the group has been canceled, reporting any error of the
+/// underlying future to its `TaskGroup` rather than returning it.
+#[must_use = "futures do nothing unless polled"]
+pub struct GroupTask<F> where F: Future {
+    inner: Arc<Inner<F::Error>>,
+    future: Option<F>,
+    id: usize,
+}
+
+struct Inner<E> {
+    state: Mutex<State<E>>,
+}
+
+struct State<E> {
+    next_id: usize,
+    // Live children, along with the task to notify if they need to be
+    // canceled.
+    children: HashMap<usize, Option<Task>>,
+    handles: usize,
+    error: Option<E>,
+    canceled: bool,
+    panicked: bool,
+    waiter: Option<Task>,
+}
+
+/// Creates a new group of tasks, returning a handle to add children to the
+/// group and a future representing the completion of all of them.
+///
+/// Children are added to the group with `GroupHandle::wrap`, which returns a
+/// future suitable for running on any executor, or `GroupHandle::execute` to
+/// submit a child directly to an `Executor`.
+///
+/// The group has a single owner, the returned `TaskGroup` future, which ties
+/// the lifetime of all children together:
+///
+/// * The `TaskGroup` resolves once all children have completed and all
+///   handles have been dropped.
+/// * The first child to fail causes the `TaskGroup` to resolve with that error
+///   and all of its siblings are canceled (dropped).
+/// * If a child panics, all of its siblings are canceled and the panic is
+///   propagated when the `TaskGroup` is next polled.
+/// * Dropping the `TaskGroup` cancels all children.
+///
+/// # Examples
+///
+/// ```
+/// use futures::Future;
+/// use futures::task;
+///
+/// let (handle, group) = task::group::<u32>();
+/// let a = handle.wrap(futures::finished(()));
+/// let b = handle.wrap(futures::failed(3));
+/// drop(handle);
+///
+/// assert_eq!(a.join(b).wait(), Ok(((), ())));
+/// assert_eq!(group.wait(), Err(3));
+/// ```
+pub fn group<E>() -> (GroupHandle<E>, TaskGroup<E>) {
+    let inner = Arc::new(Inner {
+        state: Mutex::new(State {
+            next_id: 0,
+            children: HashMap::new(),
+            handles: 1,
+            error: None,
+            canceled: false,
+            panicked: false,
+            waiter: None,
+        }),
+    });
+    let handle = GroupHandle { inner: inner.clone() };
+    let group = TaskGroup { inner: inner };
+    (handle, group)
+}
+
+impl<E> State<E> {
+    // Flags the group as canceled, returning all tasks which need to be
+    // notified as a result. Notifications are sent after the lock has been
+    // released as they may run children inline.
+    fn cancel(&mut self) -> Vec<Task> {
+        self.canceled = true;
+        let mut tasks = self.children.values_mut()
+                            .filter_map(|t| t.take())
+                            .collect::<Vec<_>>();
+        tasks.extend(self.waiter.take());
+        tasks
+    }
+}
+
+fn unpark_all(tasks: Vec<Task>) {
+    for task in tasks {
+        task.unpark();
+    }
+}
+
+impl<E> GroupHandle<E> {
+    /// Adds a new child to this group, returning a future which drives it.
+    ///
+    /// The returned future must be run to completion (typically by spawning it
+    /// onto an executor) for the group to complete. If the group has already
+    /// been canceled then the returned future will resolve immediately without
+    /// ever polling `f`.
+    pub fn wrap<F>(&self, f: F) -> GroupTask<F>
+        where F: Future<Item=(), Error=E>,
+    {
+        let mut state = self.inner.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.children.insert(id, None);
+        drop(state);
+
+        GroupTask {
+            inner: self.inner.clone(),
+            future: Some(f),
+            id: id,
+        }
+    }
+
+    /// Adds a new child to this group and submits it to run on `exec`.
+    ///
+    /// This is a convenience for `task::spawn(handle.wrap(f)).execute(exec)`.
+    pub fn execute<F>(&self, f: F, exec: Arc<Executor>)
+        where F: Future<Item=(), Error=E> + Send + 'static,
+              E: Send + 'static,
+    {
+        task::spawn(self.wrap(f)).execute(exec)
+    }
+
+    /// Returns whether the group this handle is associated with has been
+    /// canceled, either by a failing child or by dropping the `TaskGroup`.
+    pub fn is_canceled(&self) -> bool {
+        self.inner.state.lock().unwrap().canceled
+    }
+}
+
+impl<E> Clone for GroupHandle<E> {
+    fn clone(&self) -> GroupHandle<E> {
+        self.inner.state.lock().unwrap().handles += 1;
+        GroupHandle { inner: self.inner.clone() }
+    }
+}
+
+impl<E> Drop for GroupHandle<E> {
+    fn drop(&mut self) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.handles -= 1;
+        let waiter = if state.handles == 0 {
+            state.waiter.take()
+        } else {
+            None
+        };
+        drop(state);
+        if let Some(waiter) = waiter {
+            waiter.unpark();
+        }
+    }
+}
+
+impl<E> Future for TaskGroup<E> {
+    type Item = ();
+    type Error = E;
+
+    fn poll(&mut self) -> Poll<(), E> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.panicked {
+            drop(state);
+            panic!("a task in this group panicked");
+        }
+        if let Some(e) = state.error.take() {
+            return Err(e)
+        }
+        if state.children.is_empty() && state.handles == 0 {
+            return Ok(Async::Ready(()))
+        }
+        state.waiter = Some(task::park());
+        Ok(Async::NotReady)
+    }
+}
+
+impl<E> Drop for TaskGroup<E> {
+    fn drop(&mut self) {
+        let tasks = self.inner.state.lock().unwrap().cancel();
+        unpark_all(tasks);
+    }
+}
+
+impl<F: Future> GroupTask<F> {
+    fn finish(&mut self, err: Option<F::Error>) {
+        self.future = None;
+        let mut state = self.inner.state.lock().unwrap();
+        state.children.remove(&self.id);
+        let tasks = match err {
+            Some(e) if !state.canceled => {
+                state.error = Some(e);
+                state.cancel()
+            }
+            _ => state.waiter.take().into_iter().collect(),
+        };
+        drop(state);
+        unpark_all(tasks);
+    }
+}
+
+impl<F: Future<Item=()>> Future for GroupTask<F> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        if self.inner.state.lock().unwrap().canceled {
+            self.finish(None);
+            return Ok(Async::Ready(()))
+        }
+
+        let res = self.future.as_mut().expect("cannot poll GroupTask twice")
+                      .poll();
+        match res {
+            Ok(Async::NotReady) => {}
+            Ok(Async::Ready(())) => {
+                self.finish(None);
+                return Ok(Async::Ready(()))
+            }
+            Err(e) => {
+                self.finish(Some(e));
+                return Ok(Async::Ready(()))
+            }
+        }
+
+        // Register ourselves to get notified if the group is canceled. The
+        // flag is checked again under the lock as a sibling may have failed
+        // while we were being polled.
+        let mut state = self.inner.state.lock().unwrap();
+        if state.canceled {
+            drop(state);
+            self.finish(None);
+            return Ok(Async::Ready(()))
+        }
+        if let Some(slot) = state.children.get_mut(&self.id) {
+            *slot = Some(task::park());
+        }
+        Ok(Async::NotReady)
+    }
+}
+
+impl<F: Future> Drop for GroupTask<F> {
+    fn drop(&mut self) {
+        if self.future.is_none() {
+            return
+        }
+
+        // We're being dropped without having completed. If that's because the
+        // child is panicking then the whole group is torn down, otherwise the
+        // child was simply discarded by its executor.
+        let mut state = self.inner.state.lock().unwrap();
+        state.children.remove(&self.id);
+        let tasks = if thread::panicking() {
+            state.panicked = true;
+            state.cancel()
+        } else {
+            state.waiter.take().into_iter().collect()
+        };
+        drop(state);
+        unpark_all(tasks);
+    }
+}
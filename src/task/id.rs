@@ -0,0 +1,148 @@
+use std::prelude::v1::*;
+
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+
+use task::lazy;
+use task::segments::Segments;
+
+// Task ids are made up of an index and a generation. Indices are recycled once
+// the task owning them is dropped, so they stay small no matter how many tasks
+// are created over time, and each index carries a generation counter which is
+// bumped whenever it's recycled so that ids of tasks which are gone are never
+// equal to ids of live tasks.
+//
+// Recycling is safe for `TaskRc` because an index is only released when the
+// `Spawn` owning it is dropped, and a task can only run (and hence access its
+// `TaskRc` data) while its `Spawn` is alive. Two live tasks therefore never
+// share an index, and the generation additionally ensures that a `TaskRc`
+// created by a task which is gone isn't accepted by a later task which reuses
+// its index.
+//
+// Ids are allocated on every spawn, so this is done without locking. Each
+// index has a `Slot` holding its generation, and the free indices form a
+// Treiber stack linked through their slots. The slots never move, so a slot
+// can always be looked up by its index, even by a thread racing with the one
+// owning it.
+//
+// The head of the free list packs the index of the first free slot, plus one
+// so that zero means the list is empty, into its lower 32 bits, and a tag
+// which is bumped on every change into its upper 32 bits. The tag prevents a
+// thread from popping a slot which was popped and pushed again while it was
+// looking at it (the ABA problem). This limits the number of tasks alive at
+// the same time to `u32::MAX - 1`, on all platforms.
+static FREE: AtomicU64 = AtomicU64::new(0);
+// The number of indices handed out so far, including those on the free list.
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+// Allocated on first use, see `lazy::get`.
+static SLOTS: AtomicPtr<Segments<Slot>> = AtomicPtr::new(ptr::null_mut());
+
+const INDEX: u64 = 0xffff_ffff;
+const TAG: u64 = 1 << 32;
+
+#[derive(Default)]
+struct Slot {
+    generation: AtomicU64,
+    // The index of the next free slot plus one, while this one is free.
+    next: AtomicU64,
+}
+
+/// A unique identifier of a task.
+///
+/// No two tasks which are alive at the same time have the same id, and ids of
+/// tasks which have been dropped are never reused in practice. Ids are
+/// compact, however, as the numeric part of the id (see `index`) is recycled.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId {
+    index: usize,
+    generation: u64,
+}
+
+// Ownership of the index of a `TaskId`, releasing it when dropped.
+pub struct OwnedId {
+    id: TaskId,
+}
+
+impl TaskId {
+    /// Returns the index of this id.
+    ///
+    /// Indices are small integers which are unique among live tasks, but may
+    /// be reused once a task is dropped.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Debug for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TaskId({}.{})", self.index, self.generation)
+    }
+}
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.index, self.generation)
+    }
+}
+
+impl OwnedId {
+    pub fn new() -> OwnedId {
+        let mut head = FREE.load(Ordering::Acquire);
+        let index = loop {
+            let free = head & INDEX;
+            if free == 0 {
+                break NEXT.fetch_add(1, Ordering::Relaxed)
+            }
+            // The slot may be popped by another thread in the meantime, in
+            // which case its `next` is stale but the tag has changed.
+            let index = (free - 1) as usize;
+            let next = slot(index).next.load(Ordering::Relaxed);
+            let new = (head & !INDEX).wrapping_add(TAG) | next;
+            match FREE.compare_exchange(head, new,
+                                        Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => break index,
+                Err(actual) => head = actual,
+            }
+        };
+        let generation = slot(index).generation.load(Ordering::Relaxed);
+        OwnedId {
+            id: TaskId {
+                index: index,
+                generation: generation,
+            },
+        }
+    }
+
+    pub fn get(&self) -> TaskId {
+        self.id
+    }
+}
+
+impl Drop for OwnedId {
+    fn drop(&mut self) {
+        let index = self.id.index;
+        let slot = slot(index);
+        slot.generation.fetch_add(1, Ordering::Relaxed);
+        let mut head = FREE.load(Ordering::Acquire);
+        loop {
+            slot.next.store(head & INDEX, Ordering::Relaxed);
+            let new = (head & !INDEX).wrapping_add(TAG) | (index as u64 + 1);
+            match FREE.compare_exchange(head, new,
+                                        Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+}
+
+// Returns the slot for `index`, allocating it if needed.
+//
+// # Panics
+//
+// Panics if `index` doesn't fit the free list, that is if too many tasks are
+// alive.
+fn slot(index: usize) -> &'static Slot {
+    lazy::get(&SLOTS, Segments::new).get(index)
+}
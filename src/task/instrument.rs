@@ -0,0 +1,196 @@
+use std::prelude::v1::*;
+
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use task::TaskId;
+use task::lazy;
+use task::registry::Meta;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+// The installed configuration is read on every poll and unpark of an
+// instrumented task, so rather than locking it each time every thread keeps a
+// snapshot of it, which is only refreshed from `CONFIG` when `VERSION` shows
+// that the configuration has changed.
+static VERSION: AtomicUsize = AtomicUsize::new(0);
+// Allocated on first use, see `lazy::get`.
+static CONFIG: AtomicPtr<Mutex<Option<Arc<Instrument>>>> =
+    AtomicPtr::new(ptr::null_mut());
+
+thread_local!(static SNAPSHOT: RefCell<(usize, Option<Arc<Instrument>>)> =
+    const { RefCell::new((0, None)) });
+
+/// Configuration of the instrumentation of tasks, installed with
+/// `set_instrumentation`.
+///
+/// While instrumentation is installed, every newly spawned task records how
+/// many times it has been polled, how long it has spent in total being
+/// polled, and how many times it has been unparked. These statistics can be
+/// read through `Spawn::stats` or the registry of live tasks.
+///
+/// Additionally, a poll which takes longer than the slow poll threshold is
+/// logged at the warn level through the `log` crate, as this typically means
+/// that a future is performing a blocking operation, and every poll and unpark
+/// is reported to the `Observer`, if any.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use futures::task::{self, Instrument};
+///
+/// task::set_instrumentation(Some(
+///     Instrument::new().slow_poll_threshold(Duration::from_millis(10))
+/// ));
+/// ```
+#[derive(Clone, Default)]
+pub struct Instrument {
+    slow_poll: Option<Duration>,
+    observer: Option<Arc<Observer>>,
+}
+
+/// A receiver of instrumentation events, used to export metrics about tasks.
+///
+/// Observers are registered with `Instrument::observer`. Their methods are
+/// called inline while a task is polled or unparked, so they should return
+/// quickly.
+pub trait Observer: Send + Sync + 'static {
+    /// Called after each poll of a task, with the time the poll took.
+    fn polled(&self, id: TaskId, name: Option<&str>, elapsed: Duration) {
+        let _ = (id, name, elapsed);
+    }
+
+    /// Called whenever a task is unparked.
+    fn unparked(&self, id: TaskId, name: Option<&str>) {
+        let _ = (id, name);
+    }
+}
+
+/// Statistics of an instrumented task.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaskStats {
+    polls: u64,
+    poll_time: Duration,
+    unparks: u64,
+}
+
+/// Installs or removes the configuration of task instrumentation.
+///
+/// Only tasks spawned while an instrumentation is installed are instrumented.
+/// See `Instrument` for details.
+pub fn set_instrumentation(config: Option<Instrument>) {
+    INSTALLED.store(config.is_some(), Ordering::SeqCst);
+    *self::config().lock().unwrap() = config.map(Arc::new);
+    VERSION.fetch_add(1, Ordering::SeqCst);
+}
+
+impl Instrument {
+    /// Creates a new configuration which records statistics of tasks, without
+    /// logging slow polls or reporting to an observer.
+    pub fn new() -> Instrument {
+        Instrument::default()
+    }
+
+    /// Logs a warning whenever a single poll of a task takes longer than
+    /// `threshold`.
+    pub fn slow_poll_threshold(mut self, threshold: Duration) -> Instrument {
+        self.slow_poll = Some(threshold);
+        self
+    }
+
+    /// Reports the polls and unparks of tasks to `observer`.
+    pub fn observer(mut self, observer: Arc<Observer>) -> Instrument {
+        self.observer = Some(observer);
+        self
+    }
+}
+
+impl TaskStats {
+    /// Returns the number of times the task has been polled.
+    pub fn polls(&self) -> u64 {
+        self.polls
+    }
+
+    /// Returns the total time spent polling the task.
+    pub fn poll_time(&self) -> Duration {
+        self.poll_time
+    }
+
+    /// Returns the number of times the task has been unparked.
+    pub fn unparks(&self) -> u64 {
+        self.unparks
+    }
+}
+
+pub fn stats(polls: u64, poll_time: Duration, unparks: u64) -> TaskStats {
+    TaskStats {
+        polls: polls,
+        poll_time: poll_time,
+        unparks: unparks,
+    }
+}
+
+// Returns whether newly spawned tasks should be instrumented.
+pub fn installed() -> bool {
+    INSTALLED.load(Ordering::SeqCst)
+}
+
+pub fn polled(meta: &Meta, elapsed: Duration) {
+    if !meta.instrumented || !installed() {
+        return
+    }
+    let config = match current() {
+        Some(config) => config,
+        None => return,
+    };
+    if let Some(threshold) = config.slow_poll {
+        if elapsed > threshold {
+            warn!("poll of task {}{} took {:?}, which may indicate a \
+                   blocking operation", meta.id, describe(meta), elapsed);
+        }
+    }
+    if let Some(ref observer) = config.observer {
+        observer.polled(meta.id, meta.name(), elapsed);
+    }
+}
+
+pub fn unparked(meta: &Meta) {
+    if !meta.instrumented || !installed() {
+        return
+    }
+    if let Some(config) = current() {
+        if let Some(ref observer) = config.observer {
+            observer.unparked(meta.id, meta.name());
+        }
+    }
+}
+
+// Returns the installed configuration, as seen by this thread.
+fn current() -> Option<Arc<Instrument>> {
+    let version = VERSION.load(Ordering::SeqCst);
+    let refresh = || config().lock().unwrap().clone();
+    // The snapshot is gone if this is called while the thread is exiting, in
+    // which case the configuration is simply read again.
+    SNAPSHOT.try_with(|snapshot| {
+        let mut snapshot = snapshot.borrow_mut();
+        if snapshot.0 != version {
+            *snapshot = (version, refresh());
+        }
+        snapshot.1.clone()
+    }).unwrap_or_else(|_| refresh())
+}
+
+fn config() -> &'static Mutex<Option<Arc<Instrument>>> {
+    lazy::get(&CONFIG, || Mutex::new(None))
+}
+
+fn describe(meta: &Meta) -> String {
+    match meta.name() {
+        Some(name) => format!(" ({})", name),
+        None => String::new(),
+    }
+}
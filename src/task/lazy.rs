@@ -0,0 +1,28 @@
+use std::prelude::v1::*;
+
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+// Returns the value behind `ptr`, allocating it with `init` on first use.
+//
+// Globals which need more than an atomic, such as a `Mutex`, can't be built
+// in a `static` directly on the compilers this crate supports, so they're
+// allocated on first use and published through an `AtomicPtr` instead. The
+// value is never freed. If several threads race to allocate it, all but one
+// of the values are dropped again.
+pub fn get<T>(ptr: &'static AtomicPtr<T>, init: fn() -> T) -> &'static T {
+    let mut value = ptr.load(Ordering::Acquire);
+    if value.is_null() {
+        let new = Box::into_raw(Box::new(init()));
+        value = match ptr.compare_exchange(ptr::null_mut(), new,
+                                           Ordering::AcqRel,
+                                           Ordering::Acquire) {
+            Ok(_) => new,
+            Err(actual) => {
+                drop(unsafe { Box::from_raw(new) });
+                actual
+            }
+        };
+    }
+    unsafe { &*value }
+}
@@ -32,7 +32,7 @@ use std::prelude::v1::*;
 use std::cell::Cell;
 use std::fmt;
 use std::sync::Arc;
-use std::sync::atomic::{Ordering, AtomicBool, AtomicUsize, ATOMIC_USIZE_INIT};
+use std::sync::atomic::{Ordering, AtomicBool};
 use std::thread;
 
 use {BoxFuture, Poll, Future, Async};
@@ -42,22 +42,36 @@ use task::unpark_mutex::UnparkMutex;
 mod unpark_mutex;
 mod task_rc;
 mod data;
+mod group;
+mod sim;
+mod lazy;
+mod registry;
+mod instrument;
+mod id;
+mod segments;
+mod event_set;
+mod atomic_task;
 pub use self::task_rc::TaskRc;
 pub use self::data::LocalKey;
+pub use self::group::{group, TaskGroup, GroupHandle, GroupTask};
+pub use self::sim::{Simulation, Schedule, SimError, SimRng};
+pub use self::registry::{TaskInfo, TaskState};
+pub use self::registry::{enable_registry, live_tasks, dump_tasks};
+pub use self::instrument::{Instrument, Observer, TaskStats};
+pub use self::instrument::set_instrumentation;
+pub use self::id::TaskId;
+pub use self::event_set::{AtomicBitSet, GrowableBitSet, ReadyQueue};
+pub use self::event_set::{Drain, GrowableDrain, ReadyDrain};
+pub use self::atomic_task::AtomicTask;
 
 thread_local!(static CURRENT_TASK: Cell<(*const Task, *const data::LocalMap)> = {
     Cell::new((0 as *const _, 0 as *const _))
 });
 
-fn fresh_task_id() -> usize {
-    // TODO: this assert is a real bummer, need to figure out how to reuse
-    //       old IDs that are no longer in use.
-    static NEXT_ID: AtomicUsize = ATOMIC_USIZE_INIT;
-    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
-    assert!(id < usize::max_value() / 2,
-            "too many previous tasks have been allocated");
-    return id
-}
+// Whether `park` has been called during the current poll, used to implement
+// `WakeupCheck`.
+#[cfg(debug_assertions)]
+thread_local!(static PARKED: Cell<bool> = const { Cell::new(false) });
 
 fn set<F, R>(task: &Task, data: &data::LocalMap, f: F) -> R
     where F: FnOnce() -> R
@@ -95,9 +109,10 @@ fn with<F: FnOnce(&Task, &data::LocalMap) -> R, R>(f: F) -> R {
 /// This is obtained by the `task::park` function.
 #[derive(Clone)]
 pub struct Task {
-    id: usize,
+    id: TaskId,
     unpark: Arc<Unpark>,
     events: Events,
+    meta: Option<Arc<registry::Meta>>,
 }
 
 fn _assert_kinds() {
@@ -132,9 +147,39 @@ fn _assert_kinds() {
 /// is, this method can be dangerous to call outside of an implementation of
 /// `poll`.
 pub fn park() -> Task {
+    note_park();
     with(|task, _| task.clone())
 }
 
+#[cfg(debug_assertions)]
+fn note_park() {
+    PARKED.with(|p| p.set(true));
+}
+
+#[cfg(not(debug_assertions))]
+fn note_park() {}
+
+// Runs `f`, returning its result along with whether `park` was called while
+// it ran. Nested polls are tracked independently of the enclosing one.
+#[cfg(debug_assertions)]
+fn track_park<F: FnOnce() -> R, R>(f: F) -> (R, bool) {
+    struct Reset(bool);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            PARKED.with(|p| p.set(self.0));
+        }
+    }
+
+    let _reset = Reset(PARKED.with(|p| p.replace(false)));
+    let r = f();
+    (r, PARKED.with(|p| p.get()))
+}
+
+#[cfg(not(debug_assertions))]
+fn track_park<F: FnOnce() -> R, R>(f: F) -> (R, bool) {
+    (f(), true)
+}
+
 impl Task {
     /// Indicate that the task should attempt to poll its future in a timely
     /// fashion. This is typically done when alerting a future that an event of
@@ -146,6 +191,9 @@ impl Task {
     /// must poll the future *again* afterwards, ensuring that all relevant
     /// events are eventually observed by the future.
     pub fn unpark(&self) {
+        if let Some(ref meta) = self.meta {
+            meta.unparked();
+        }
         self.events.trigger();
         self.unpark.unpark();
     }
@@ -156,13 +204,32 @@ impl Task {
     pub fn is_current(&self) -> bool {
         with(|current, _| current.id == self.id)
     }
+
+    /// Returns the id of this task.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// Returns the name given to this task when it was spawned with a
+    /// `Builder`, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.meta.as_ref().and_then(|m| m.name())
+    }
 }
 
 impl fmt::Debug for Task {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("Task")
-         .field("id", &self.id)
-         .finish()
+        let mut d = f.debug_struct("Task");
+        d.field("id", &self.id);
+        if let Some(ref meta) = self.meta {
+            if let Some(ref name) = meta.name {
+                d.field("name", name);
+            }
+            if !meta.metadata.is_empty() {
+                d.field("metadata", &meta.metadata);
+            }
+        }
+        d.finish()
     }
 }
 
@@ -178,8 +245,23 @@ impl fmt::Debug for Task {
 /// `Spawn` depending which is used.
 pub struct Spawn<T> {
     obj: T,
-    id: usize,
+    id: id::OwnedId,
     data: data::LocalMap,
+    check: WakeupCheck,
+    meta: Option<Arc<registry::Meta>>,
+    registration: Option<registry::Registration>,
+}
+
+/// Configuration of how a `Spawn` reacts to lost wakeups, see
+/// `Spawn::check_wakeups`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WakeupCheck {
+    /// Polls are not checked, the default.
+    Off,
+    /// A lost wakeup is reported through the `log` crate at the error level.
+    Log,
+    /// A lost wakeup causes a panic.
+    Panic,
 }
 
 /// Spawns a new future, returning the fused future and task.
@@ -192,10 +274,88 @@ pub struct Spawn<T> {
 /// attempt to run code in the background. The future will not make progress
 /// until the methods on `Spawn` are called in turn.
 pub fn spawn<T>(obj: T) -> Spawn<T> {
-    Spawn {
-        obj: obj,
-        id: fresh_task_id(),
-        data: data::local_map(),
+    Builder::new().spawn(obj)
+}
+
+/// Task factory, which can be used to configure the properties of a new task.
+///
+/// Methods can be chained on it in order to configure it, and then `spawn`
+/// is used to create the task, like with the `spawn` function in this module.
+/// The name and metadata given to a task are shown by the `Debug` output of
+/// its `Task` handles and recorded in the registry of live tasks, if enabled
+/// (see `enable_registry`).
+///
+/// Tasks spawned while an `Instrument` configuration is installed record
+/// statistics about their polls, see `set_instrumentation` for details.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{Future, finished};
+/// use futures::task;
+///
+/// let mut task = task::Builder::new()
+///     .name("worker".to_string())
+///     .metadata("shard", "3")
+///     .spawn(futures::lazy(|| {
+///         assert_eq!(task::park().name(), Some("worker"));
+///         finished::<(), ()>(())
+///     }));
+/// task.wait_future().unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct Builder {
+    name: Option<String>,
+    metadata: Vec<(String, String)>,
+}
+
+impl Builder {
+    /// Creates a new builder for a task with no name or metadata.
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+
+    /// Names the task to be spawned, for identification in debug output.
+    pub fn name(mut self, name: String) -> Builder {
+        self.name = Some(name);
+        self
+    }
+
+    /// Attaches a key/value pair of metadata to the task to be spawned, for
+    /// identification in debug output.
+    pub fn metadata(mut self, key: &str, value: &str) -> Builder {
+        self.metadata.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Spawns a new task with this configuration, returning the fused future
+    /// and task. See the `spawn` function for details.
+    pub fn spawn<T>(self, obj: T) -> Spawn<T> {
+        let id = id::OwnedId::new();
+        let register = registry::enabled();
+        let instrumented = instrument::installed();
+        let meta = if register || instrumented || self.name.is_some() ||
+                      !self.metadata.is_empty() {
+            Some(Arc::new(registry::Meta::new(id.get(),
+                                              self.name,
+                                              self.metadata,
+                                              instrumented,
+                                              register)))
+        } else {
+            None
+        };
+        let registration = match meta {
+            Some(ref meta) if register => Some(registry::register(meta.clone())),
+            _ => None,
+        };
+        Spawn {
+            obj: obj,
+            id: id,
+            data: data::local_map(),
+            check: WakeupCheck::Off,
+            meta: meta,
+            registration: registration,
+        }
     }
 }
 
@@ -214,7 +374,7 @@ impl<F: Future> Spawn<F> {
     /// Otherwise if `Ready` or `Err` is returned, the `Spawn` task can be
     /// safely destroyed.
     pub fn poll_future(&mut self, unpark: Arc<Unpark>) -> Poll<F::Item, F::Error> {
-        self.enter(unpark, |f| f.poll())
+        self.poll_with(unpark, |f| f.poll(), |_| true)
     }
 
     /// Waits for the internal future to complete, blocking this thread's
@@ -259,6 +419,9 @@ impl<F: Future> Spawn<F> {
             spawn: Spawn {
                 id: self.id,
                 data: self.data,
+                check: self.check,
+                meta: self.meta,
+                registration: self.registration,
                 obj: self.obj.boxed(),
             },
             inner: Arc::new(Inner {
@@ -273,7 +436,7 @@ impl<S: Stream> Spawn<S> {
     /// Like `poll_future`, except polls the underlying stream.
     pub fn poll_stream(&mut self, unpark: Arc<Unpark>)
                        -> Poll<Option<S::Item>, S::Error> {
-        self.enter(unpark, |stream| stream.poll())
+        self.poll_with(unpark, |s| s.poll(), |item| item.is_none())
     }
 
     /// Like `wait_future`, except only waits for the next element to arrive on
@@ -292,19 +455,100 @@ impl<S: Stream> Spawn<S> {
 }
 
 impl<T> Spawn<T> {
+    /// Returns the id of this task.
+    pub fn id(&self) -> TaskId {
+        self.id.get()
+    }
+
+    /// Returns the statistics of this task, or `None` if it isn't
+    /// instrumented.
+    ///
+    /// Tasks are instrumented if they were spawned while an `Instrument`
+    /// configuration was installed, see `set_instrumentation`.
+    pub fn stats(&self) -> Option<TaskStats> {
+        self.meta.as_ref().and_then(|m| m.stats())
+    }
+
+    /// Configures whether polls of this task are checked for lost wakeups.
+    ///
+    /// A future or stream which returns `NotReady` without having arranged
+    /// for the task to be unparked later will never be polled again, which
+    /// typically manifests as a silent hang. The most common cause is
+    /// returning `NotReady` without `task::park` having been called at all.
+    /// With checking enabled, `poll_future` and `poll_stream` track whether
+    /// `task::park` was called (by the top-level object or anything it polls)
+    /// during a poll which returned `NotReady`, and if it wasn't the task id
+    /// is either logged or a panic is raised, depending on `check`.
+    ///
+    /// Checking only happens in builds with debug assertions enabled; in
+    /// release builds this setting has no effect.
+    ///
+    /// Note that this check has false positives: some objects legitimately
+    /// return `NotReady` without parking, for example `empty()`, which never
+    /// completes, or objects that are unparked through means other than
+    /// `task::park`, such as a `Spawn` polled manually with a custom
+    /// `Unpark`. It also can't detect a task handle which was obtained but
+    /// then dropped without being stored.
+    pub fn check_wakeups(&mut self, check: WakeupCheck) {
+        self.check = check;
+    }
+
+    // Polls the object with `f`, where `done` determines whether a `Ready`
+    // value means the object has completed.
+    fn poll_with<F, R, E>(&mut self,
+                          unpark: Arc<Unpark>,
+                          f: F,
+                          done: fn(&R) -> bool) -> Poll<R, E>
+        where F: FnOnce(&mut T) -> Poll<R, E>
+    {
+        let start = self.meta.as_ref().and_then(|m| m.start_poll());
+        let check = self.check;
+        let (res, parked) = self.enter(unpark, |obj| track_park(|| f(obj)));
+        let state = match res {
+            Ok(Async::NotReady) => {
+                lost_wakeup(check, parked, self.id.get());
+                TaskState::Parked
+            }
+            Ok(Async::Ready(ref r)) if !done(r) => TaskState::Idle,
+            _ => TaskState::Completed,
+        };
+        if let (Some(meta), Some(start)) = (self.meta.as_ref(), start) {
+            meta.end_poll(start, state);
+        }
+        res
+    }
+
     fn enter<F, R>(&mut self, unpark: Arc<Unpark>, f: F) -> R
         where F: FnOnce(&mut T) -> R
     {
         let task = Task {
-            id: self.id,
+            id: self.id.get(),
             unpark: unpark,
             events: Events::new(),
+            meta: self.meta.clone(),
         };
         let obj = &mut self.obj;
         set(&task, &self.data, || f(obj))
     }
 }
 
+fn lost_wakeup(check: WakeupCheck, parked: bool, id: TaskId) {
+    if parked {
+        return
+    }
+    match check {
+        WakeupCheck::Off => {}
+        WakeupCheck::Log => {
+            error!("task {} returned NotReady without calling task::park, \
+                    it may never be woken up", id);
+        }
+        WakeupCheck::Panic => {
+            panic!("task {} returned NotReady without calling task::park, \
+                    it may never be woken up", id);
+        }
+    }
+}
+
 /// A trait which represents a sink of notifications that a future is ready to
 /// make progress.
 ///
@@ -436,6 +680,7 @@ pub fn with_unpark_event<F, R>(event: UnparkEvent, f: F) -> R
             id: task.id,
             unpark: task.unpark.clone(),
             events: task.events.with_event(event),
+            meta: task.meta.clone(),
         };
         set(&new_task, data, f)
     })
@@ -462,6 +707,10 @@ impl UnparkEvent {
             item: id,
         }
     }
+
+    fn trigger(&self) {
+        self.set.insert(self.item)
+    }
 }
 
 /// A concurrent set which allows for the insertion of `usize` values.
@@ -473,26 +722,88 @@ pub trait EventSet: Send + Sync + 'static {
     fn insert(&self, id: usize);
 }
 
-// A collection of UnparkEvents to trigger on `unpark`
+// A collection of UnparkEvents to trigger on `unpark`.
+//
+// This is cloned every time `task::park` is called, and extended on every
+// (possibly nested) call to `with_unpark_event`, so both need to be cheap. Up
+// to two events are stored inline, which covers the vast majority of uses
+// without allocating. Beyond that events are kept in a persistent linked list
+// which is shared between all clones, so cloning stays O(1) regardless of how
+// deeply `with_unpark_event` is nested, and adding an event allocates a single
+// node.
 #[derive(Clone)]
-struct Events {
-    set: Vec<UnparkEvent>, // TODO: change to some SmallVec
+enum Events {
+    Zero,
+    One(UnparkEvent),
+    Two(UnparkEvent, UnparkEvent),
+    Many(Arc<EventNode>),
+}
+
+struct EventNode {
+    event: UnparkEvent,
+    next: Option<Arc<EventNode>>,
 }
 
 impl Events {
     fn new() -> Events {
-        Events { set: Vec::new() }
+        Events::Zero
     }
 
     fn trigger(&self) {
-        for event in self.set.iter() {
-            event.set.insert(event.item)
+        match *self {
+            Events::Zero => {}
+            Events::One(ref a) => a.trigger(),
+            Events::Two(ref a, ref b) => {
+                a.trigger();
+                b.trigger();
+            }
+            Events::Many(ref node) => {
+                let mut cur = Some(node);
+                while let Some(node) = cur {
+                    node.event.trigger();
+                    cur = node.next.as_ref();
+                }
+            }
         }
     }
 
     fn with_event(&self, event: UnparkEvent) -> Events {
-        let mut set = self.set.clone();
-        set.push(event);
-        Events { set: set }
+        match *self {
+            Events::Zero => Events::One(event),
+            Events::One(ref a) => Events::Two(a.clone(), event),
+            Events::Two(ref a, ref b) => {
+                let a = Arc::new(EventNode { event: a.clone(), next: None });
+                let b = Arc::new(EventNode { event: b.clone(), next: Some(a) });
+                Events::Many(Arc::new(EventNode { event: event, next: Some(b) }))
+            }
+            Events::Many(ref node) => {
+                Events::Many(Arc::new(EventNode {
+                    event: event,
+                    next: Some(node.clone()),
+                }))
+            }
+        }
+    }
+}
+
+// Gives `benches/unpark_events.rs` access to the representation of unpark
+// events. This isn't part of the public API.
+#[doc(hidden)]
+pub mod __bench {
+    use super::UnparkEvent;
+
+    #[derive(Clone)]
+    pub struct Events(super::Events);
+
+    impl Default for Events {
+        fn default() -> Events {
+            Events(super::Events::new())
+        }
+    }
+
+    impl Events {
+        pub fn with_event(&self, event: UnparkEvent) -> Events {
+            Events(self.0.with_event(event))
+        }
     }
 }
@@ -0,0 +1,285 @@
+use std::prelude::v1::*;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use task::TaskId;
+use task::instrument::{self, TaskStats};
+use task::lazy;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// Allocated on first use, see `lazy::get`.
+static REGISTRY: AtomicPtr<Registry> = AtomicPtr::new(ptr::null_mut());
+
+type Registry = Mutex<HashMap<TaskId, Arc<Meta>>>;
+
+// Descriptive information about a task, shared between its `Spawn`, the
+// `Task` handles for it and the registry.
+pub struct Meta {
+    pub id: TaskId,
+    pub name: Option<String>,
+    pub metadata: Vec<(String, String)>,
+    pub instrumented: bool,
+    registered: bool,
+    status: Mutex<Status>,
+    unparks: AtomicUsize,
+}
+
+struct Status {
+    state: TaskState,
+    last_poll: Option<Instant>,
+    polls: u64,
+    poll_time: Duration,
+}
+
+/// The state of a task in the registry of live tasks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskState {
+    /// The task isn't being polled and isn't waiting to be unparked, for
+    /// example because it hasn't been polled yet or because its stream has
+    /// just yielded an item.
+    Idle,
+    /// The task is currently being polled.
+    Running,
+    /// The task's last poll returned `NotReady`, so it's waiting to be
+    /// unparked.
+    Parked,
+    /// The task's future or stream has resolved.
+    Completed,
+}
+
+/// A snapshot of the state of a live task, as returned by `live_tasks`.
+#[derive(Clone)]
+pub struct TaskInfo {
+    id: TaskId,
+    name: Option<String>,
+    metadata: Vec<(String, String)>,
+    state: TaskState,
+    last_poll: Option<Instant>,
+    stats: Option<TaskStats>,
+}
+
+// Removes a task from the registry once its `Spawn` goes away.
+pub struct Registration {
+    meta: Arc<Meta>,
+}
+
+/// Enables or disables the global registry of live tasks.
+///
+/// While the registry is enabled every newly spawned task is recorded, along
+/// with its name, metadata and current state, until it's dropped. The
+/// registry can then be inspected with `live_tasks` or `dump_tasks` to find
+/// out which tasks are alive and what they're doing, for example when
+/// debugging a hang.
+///
+/// The registry is disabled by default as it adds some synchronization to
+/// every spawn and poll. Only tasks spawned while it's enabled are recorded,
+/// and disabling it forgets all recorded tasks.
+pub fn enable_registry(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+    if !enabled {
+        registry().lock().unwrap().clear();
+    }
+}
+
+/// Returns a snapshot of all tasks in the registry of live tasks, ordered by
+/// task id.
+///
+/// This returns an empty list unless the registry has been turned on with
+/// `enable_registry`.
+pub fn live_tasks() -> Vec<TaskInfo> {
+    let metas = registry().lock().unwrap()
+                          .values()
+                          .cloned()
+                          .collect::<Vec<_>>();
+    let mut tasks = metas.iter().map(|meta| {
+        let status = meta.status.lock().unwrap();
+        TaskInfo {
+            id: meta.id,
+            name: meta.name.clone(),
+            metadata: meta.metadata.clone(),
+            state: status.state,
+            last_poll: status.last_poll,
+            stats: meta.stats_locked(&status),
+        }
+    }).collect::<Vec<_>>();
+    tasks.sort_by_key(|t| t.id);
+    tasks
+}
+
+/// Returns a human readable description of every task in the registry of
+/// live tasks, one per line.
+pub fn dump_tasks() -> String {
+    let now = Instant::now();
+    let mut out = String::new();
+    for task in live_tasks() {
+        out.push_str(&format!("task {}", task.id));
+        if let Some(ref name) = task.name {
+            out.push_str(&format!(" ({})", name));
+        }
+        out.push_str(&format!(": {:?}", task.state));
+        match task.last_poll {
+            Some(t) => out.push_str(&format!(", last polled {:?} ago",
+                                             now.duration_since(t))),
+            None => out.push_str(", never polled"),
+        }
+        if let Some(stats) = task.stats {
+            out.push_str(&format!(", {} polls taking {:?}, {} unparks",
+                                  stats.polls(),
+                                  stats.poll_time(),
+                                  stats.unparks()));
+        }
+        for (key, value) in task.metadata.iter() {
+            out.push_str(&format!(", {}={}", key, value));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+impl Meta {
+    pub fn new(id: TaskId,
+               name: Option<String>,
+               metadata: Vec<(String, String)>,
+               instrumented: bool,
+               registered: bool) -> Meta {
+        Meta {
+            id: id,
+            name: name,
+            metadata: metadata,
+            instrumented: instrumented,
+            registered: registered,
+            status: Mutex::new(Status {
+                state: TaskState::Idle,
+                last_poll: None,
+                polls: 0,
+                poll_time: Duration::new(0, 0),
+            }),
+            unparks: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| &s[..])
+    }
+
+    // Records the start of a poll, returning the time it started, or `None`
+    // if nobody is interested in the polls of this task. A task which is only
+    // named isn't tracked at all, and neither is a registered one once the
+    // registry has been disabled again.
+    pub fn start_poll(&self) -> Option<Instant> {
+        if !(self.instrumented || self.registered && enabled()) {
+            return None
+        }
+        let now = Instant::now();
+        let mut status = self.status.lock().unwrap();
+        status.state = TaskState::Running;
+        status.last_poll = Some(now);
+        Some(now)
+    }
+
+    // Records the end of a poll which started at `start` and left the task in
+    // `state`.
+    pub fn end_poll(&self, start: Instant, state: TaskState) {
+        let elapsed = start.elapsed();
+        {
+            let mut status = self.status.lock().unwrap();
+            status.state = state;
+            status.polls += 1;
+            status.poll_time += elapsed;
+        }
+        instrument::polled(self, elapsed);
+    }
+
+    pub fn unparked(&self) {
+        if !self.instrumented {
+            return
+        }
+        self.unparks.fetch_add(1, Ordering::SeqCst);
+        instrument::unparked(self);
+    }
+
+    pub fn stats(&self) -> Option<TaskStats> {
+        self.stats_locked(&self.status.lock().unwrap())
+    }
+
+    fn stats_locked(&self, status: &Status) -> Option<TaskStats> {
+        if !self.instrumented {
+            return None
+        }
+        Some(instrument::stats(status.polls,
+                               status.poll_time,
+                               self.unparks.load(Ordering::SeqCst) as u64))
+    }
+}
+
+// Returns whether new tasks should be registered.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+pub fn register(meta: Arc<Meta>) -> Registration {
+    registry().lock().unwrap().insert(meta.id, meta.clone());
+    Registration { meta: meta }
+}
+
+fn registry() -> &'static Registry {
+    lazy::get(&REGISTRY, || Mutex::new(HashMap::new()))
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        registry().lock().unwrap().remove(&self.meta.id);
+    }
+}
+
+impl TaskInfo {
+    /// Returns the id of this task.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// Returns the name given to this task by `Builder::name`, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| &s[..])
+    }
+
+    /// Returns the metadata attached to this task by `Builder::metadata`.
+    pub fn metadata(&self) -> &[(String, String)] {
+        &self.metadata
+    }
+
+    /// Returns the state of this task at the time the snapshot was taken.
+    pub fn state(&self) -> TaskState {
+        self.state
+    }
+
+    /// Returns the statistics of this task, or `None` if it isn't
+    /// instrumented (see `set_instrumentation`).
+    pub fn stats(&self) -> Option<TaskStats> {
+        self.stats
+    }
+
+    /// Returns how long ago this task was last polled, or `None` if it hasn't
+    /// been polled yet.
+    pub fn since_last_poll(&self) -> Option<Duration> {
+        self.last_poll.map(|t| Instant::now().duration_since(t))
+    }
+}
+
+impl fmt::Debug for TaskInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TaskInfo")
+         .field("id", &self.id)
+         .field("name", &self.name)
+         .field("metadata", &self.metadata)
+         .field("state", &self.state)
+         .field("since_last_poll", &self.since_last_poll())
+         .field("stats", &self.stats)
+         .finish()
+    }
+}
@@ -0,0 +1,101 @@
+use std::prelude::v1::*;
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+// The number of segments, enough for `u32::MAX` elements.
+pub const SEGMENTS: usize = 32;
+
+// A growable array whose elements never move, so that it can be shared
+// between threads without a lock.
+//
+// The elements are allocated in segments on first use, segment `n` holding the
+// `2^n` elements starting at index `2^n - 1`. Segments are only freed when the
+// array is dropped, so references to elements stay valid while other threads
+// grow the array.
+pub struct Segments<T> {
+    segments: [AtomicPtr<T>; SEGMENTS],
+    _marker: PhantomData<T>,
+}
+
+impl<T: Default> Segments<T> {
+    pub fn new() -> Segments<T> {
+        Segments::default()
+    }
+
+    // Returns the element at `index`, allocating its segment if needed.
+    //
+    // # Panics
+    //
+    // Panics if `index` is `u32::MAX` or larger.
+    pub fn get(&self, index: usize) -> &T {
+        let (n, offset) = locate(index);
+        let ptr = &self.segments[n];
+        let mut segment = ptr.load(Ordering::Acquire);
+        if segment.is_null() {
+            let new = (0..1usize << n).map(|_| T::default())
+                                      .collect::<Vec<_>>()
+                                      .into_boxed_slice();
+            let new = Box::into_raw(new) as *mut T;
+            segment = match ptr.compare_exchange(ptr::null_mut(), new,
+                                                 Ordering::AcqRel,
+                                                 Ordering::Acquire) {
+                Ok(_) => new,
+                Err(actual) => {
+                    drop(unsafe { free(new, n) });
+                    actual
+                }
+            };
+        }
+        unsafe { &*segment.add(offset) }
+    }
+}
+
+impl<T> Default for Segments<T> {
+    fn default() -> Segments<T> {
+        Segments {
+            segments: Default::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Segments<T> {
+    // Returns the elements of segment `n` if it has been allocated. The first
+    // of them is at index `2^n - 1`.
+    pub fn segment(&self, n: usize) -> Option<&[T]> {
+        let segment = self.segments[n].load(Ordering::Acquire);
+        if segment.is_null() {
+            None
+        } else {
+            Some(unsafe { slice::from_raw_parts(segment, 1 << n) })
+        }
+    }
+}
+
+impl<T> Drop for Segments<T> {
+    fn drop(&mut self) {
+        for (n, ptr) in self.segments.iter().enumerate() {
+            let segment = ptr.load(Ordering::Relaxed);
+            if !segment.is_null() {
+                drop(unsafe { free(segment, n) });
+            }
+        }
+    }
+}
+
+// Returns the segment holding `index`, and the offset of `index` in it.
+fn locate(index: usize) -> (usize, usize) {
+    assert!(index < u32::MAX as usize, "index {} out of range", index);
+    let i = index + 1;
+    let n = mem::size_of::<usize>() * 8 - 1 - i.leading_zeros() as usize;
+    (n, i - (1 << n))
+}
+
+// Reclaims segment `n`, which is at `segment`.
+unsafe fn free<T>(segment: *mut T, n: usize) -> Box<[T]> {
+    Box::from_raw(ptr::slice_from_raw_parts_mut(segment, 1 << n))
+}
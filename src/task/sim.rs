@@ -0,0 +1,246 @@
+use std::prelude::v1::*;
+
+use std::sync::{Arc, Mutex};
+
+use {Future, Async};
+use task::{self, Spawn, Unpark};
+
+/// A single-threaded executor which runs tasks in a deterministic, seeded
+/// order, intended for testing concurrent code.
+///
+/// Bugs in code built on primitives like `oneshot` or `stream::channel` often
+/// only show up under particular interleavings of tasks. A `Simulation` runs
+/// many tasks on the current thread and, whenever more than one task is
+/// runnable, uses a random number generator seeded by the caller to pick which
+/// one to poll next. Running the same set of tasks with the same seed always
+/// produces the same interleaving, so exploring many seeds exercises many
+/// interleavings and any failing seed can be reproduced.
+///
+/// Every decision made is recorded in a `Schedule`, which can also be passed
+/// to `Simulation::replay` to re-run the exact same interleaving.
+///
+/// Note that the determinism of a simulation relies on tasks only being
+/// unparked by other tasks of the same simulation. `run` never waits for
+/// notifications from other threads: once no task is runnable it returns
+/// `SimError::Deadlock`, even if another thread would later unpark one of the
+/// remaining tasks. Such an unpark isn't lost though, and calling `run` again
+/// afterwards polls the unparked task.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{Future, oneshot};
+/// use futures::task::Simulation;
+///
+/// for seed in 0..10 {
+///     let (tx, rx) = oneshot::<u32>();
+///     let mut sim = Simulation::new(seed);
+///     sim.spawn(rx.map(|v| assert_eq!(v, 1)).map_err(|_| ()));
+///     sim.spawn(futures::lazy(move || {
+///         tx.complete(1);
+///         Ok(())
+///     }));
+///     sim.run().unwrap();
+/// }
+/// ```
+pub struct Simulation {
+    rng: SimRng,
+    replay: Option<Schedule>,
+    schedule: Schedule,
+    tasks: Vec<Option<SimTask>>,
+    ready: Arc<Mutex<Vec<bool>>>,
+}
+
+type SimTask = Spawn<Box<Future<Item=(), Error=()>>>;
+
+/// A record of the scheduling decisions made by a `Simulation`.
+///
+/// Each step of a schedule is the identifier (as returned by
+/// `Simulation::spawn`) of the task which was polled at that step.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Schedule {
+    steps: Vec<usize>,
+}
+
+/// Errors which can happen while running a `Simulation`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SimError {
+    /// No task can make progress, but the listed tasks have not completed
+    /// yet.
+    Deadlock(Vec<usize>),
+
+    /// While replaying a schedule, the task which the schedule says to poll at
+    /// the given step was not runnable, meaning the tasks under test don't
+    /// behave the same way as when the schedule was recorded.
+    Diverged(usize),
+}
+
+struct SimUnpark {
+    id: usize,
+    ready: Arc<Mutex<Vec<bool>>>,
+}
+
+impl Unpark for SimUnpark {
+    fn unpark(&self) {
+        self.ready.lock().unwrap()[self.id] = true;
+    }
+}
+
+impl Simulation {
+    /// Creates a new simulation which makes its scheduling decisions based on
+    /// `seed`.
+    pub fn new(seed: u64) -> Simulation {
+        Simulation {
+            rng: SimRng::new(seed),
+            replay: None,
+            schedule: Schedule::default(),
+            tasks: Vec::new(),
+            ready: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Creates a new simulation which makes exactly the decisions recorded in
+    /// `schedule`, typically taken from a previous run which failed.
+    ///
+    /// The same tasks must be spawned, in the same order, as for the run which
+    /// recorded the schedule. Once the schedule is exhausted the simulation
+    /// falls back to always polling the runnable task with the lowest
+    /// identifier.
+    pub fn replay(schedule: Schedule) -> Simulation {
+        let mut sim = Simulation::new(0);
+        sim.replay = Some(schedule);
+        sim
+    }
+
+    /// Adds a new task to this simulation, returning its identifier.
+    ///
+    /// The task is runnable immediately, but won't be polled until `run` is
+    /// called.
+    pub fn spawn<F>(&mut self, f: F) -> usize
+        where F: Future<Item=(), Error=()> + 'static,
+    {
+        let id = self.tasks.len();
+        self.tasks.push(Some(task::spawn(Box::new(f))));
+        self.ready.lock().unwrap().push(true);
+        id
+    }
+
+    /// Runs all tasks in this simulation until they've completed.
+    ///
+    /// Returns an error if the simulation deadlocks, that is if no task is
+    /// runnable while some tasks haven't completed, or if a replayed schedule
+    /// diverges. After a deadlock the simulation can be run again, continuing
+    /// with any tasks which have since been unparked.
+    ///
+    /// # Panics
+    ///
+    /// Panics raised by tasks propagate out of this function. The schedule up
+    /// to that point is still available through `schedule`.
+    pub fn run(&mut self) -> Result<(), SimError> {
+        loop {
+            let runnable = {
+                let ready = self.ready.lock().unwrap();
+                (0..self.tasks.len()).filter(|&i| {
+                    ready[i] && self.tasks[i].is_some()
+                }).collect::<Vec<_>>()
+            };
+            if runnable.is_empty() {
+                let pending = (0..self.tasks.len()).filter(|&i| {
+                    self.tasks[i].is_some()
+                }).collect::<Vec<_>>();
+                if pending.is_empty() {
+                    return Ok(())
+                }
+                return Err(SimError::Deadlock(pending))
+            }
+
+            let step = self.schedule.steps.len();
+            let id = match self.replay {
+                Some(ref replay) => {
+                    match replay.steps.get(step) {
+                        Some(&id) if runnable.contains(&id) => id,
+                        Some(_) => return Err(SimError::Diverged(step)),
+                        None => runnable[0],
+                    }
+                }
+                None => runnable[self.rng.next_u64() as usize % runnable.len()],
+            };
+            self.schedule.steps.push(id);
+            self.ready.lock().unwrap()[id] = false;
+
+            let unpark = Arc::new(SimUnpark {
+                id: id,
+                ready: self.ready.clone(),
+            });
+            let done = match self.tasks[id].as_mut().unwrap().poll_future(unpark) {
+                Ok(Async::NotReady) => false,
+                Ok(Async::Ready(())) | Err(()) => true,
+            };
+            if done {
+                self.tasks[id] = None;
+            }
+        }
+    }
+
+    /// Returns the scheduling decisions made so far by this simulation.
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+}
+
+impl Schedule {
+    /// Creates a schedule from a list of task identifiers.
+    pub fn new(steps: Vec<usize>) -> Schedule {
+        Schedule { steps: steps }
+    }
+
+    /// Returns the identifier of the task polled at each step.
+    pub fn steps(&self) -> &[usize] {
+        &self.steps
+    }
+}
+
+/// The random number generator used by `Simulation` to make its scheduling
+/// decisions.
+///
+/// This is a small xorshift generator, which is cheap and produces the same
+/// sequence for a given seed on every platform. It's exposed so that test
+/// utilities which take a seed, such as those in the `futures-test` crate,
+/// make their decisions from the same sequence as a simulation would.
+///
+/// # Examples
+///
+/// ```
+/// use futures::task::SimRng;
+///
+/// let mut a = SimRng::new(3);
+/// let mut b = SimRng::new(3);
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    /// Creates a new generator from `seed`.
+    pub fn new(seed: u64) -> SimRng {
+        // Run the seed through splitmix64 so that small or zero seeds still
+        // produce a good initial state.
+        let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        SimRng { state: if z == 0 { 1 } else { z } }
+    }
+
+    /// Returns the next number in the sequence.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+}
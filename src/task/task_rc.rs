@@ -2,6 +2,8 @@ use std::prelude::v1::*;
 use std::sync::Arc;
 use std::cell::UnsafeCell;
 
+use task::TaskId;
+
 // One critical piece of this module's contents are the `TaskRc<A>` handles.
 // The purpose of this is to conceptually be able to store data in a task,
 // allowing it to be accessed within multiple futures at once. For example if
@@ -39,15 +41,19 @@ use std::cell::UnsafeCell;
 // methods below are indeed safe. The data is always valid as it's stored in an
 // `Arc`, and access is only allowed with the proof of the associated `Task`.
 // One thing you might be asking yourself though is what exactly is this "proof
-// of a task"? Right now it's a `usize` corresponding to the `Task`'s
-// `TaskHandle` arc allocation.
+// of a task"? Right now it's the `TaskId` of the task which created the data.
+// No two live tasks share an id, so only one task at a time can ever access
+// the data.
 //
-// Wait a minute, isn't that the ABA problem! That is, we create a task A, add
-// some data to it, destroy task A, do some work, create a task B, and then ask
-// to get the data from task B. In this case though the point of the
-// `task_inner` "proof" field is simply that there's some non-`Sync` token
-// proving that you can get access to the data. So while weird, this case should
-// still be safe, as the data's not stored in the task itself.
+// Wait a minute, isn't that the ABA problem! Task ids are recycled after all.
+// That is, we create a task A, add some data to it, destroy task A, do some
+// work, create a task B which reuses the index of A's id, and then ask to get
+// the data from task B. Ids carry a generation which is bumped whenever their
+// index is recycled, so B's id is still different from A's and the access
+// panics. Even if that weren't the case, though, the point of the id "proof"
+// is simply that there's some non-`Sync` token proving that you can get access
+// to the data. So while weird, the access would still be safe as A can no
+// longer run, and the data's not stored in the task itself.
 
 /// A reference to a piece of data that's accessible only within a specific
 /// `Task`.
@@ -56,7 +62,7 @@ use std::cell::UnsafeCell;
 /// within is accessed in a single-threaded way. The thread accessing it may
 /// change over time, if the task migrates, so `A` must be `Send`.
 pub struct TaskRc<A> {
-    task_id: usize,
+    task_id: TaskId,
     ptr: Arc<UnsafeCell<A>>,
 }
 
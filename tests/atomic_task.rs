@@ -0,0 +1,109 @@
+extern crate futures;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use futures::{Future, Poll, Async};
+use futures::task::{self, AtomicTask, Unpark};
+
+mod support;
+use support::*;
+
+struct Count(AtomicUsize);
+
+impl Unpark for Count {
+    fn unpark(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn register(atomic: &Arc<AtomicTask>, unpark: Arc<Unpark>) {
+    let atomic = atomic.clone();
+    let mut t = task::spawn(futures::lazy(move || {
+        atomic.register();
+        Ok::<(), ()>(())
+    }));
+    t.poll_future(unpark).unwrap();
+}
+
+#[test]
+fn notify_unparks_once() {
+    let atomic = Arc::new(AtomicTask::new());
+    atomic.notify();
+
+    let count = Arc::new(Count(AtomicUsize::new(0)));
+    register(&atomic, count.clone());
+    assert_eq!(count.0.load(Ordering::SeqCst), 0);
+    atomic.notify();
+    assert_eq!(count.0.load(Ordering::SeqCst), 1);
+    atomic.notify();
+    assert_eq!(count.0.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn register_replaces() {
+    let atomic = Arc::new(AtomicTask::new());
+    register(&atomic, unpark_panic());
+    let count = Arc::new(Count(AtomicUsize::new(0)));
+    register(&atomic, count.clone());
+    atomic.notify();
+    assert_eq!(count.0.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn take() {
+    let atomic = Arc::new(AtomicTask::new());
+    assert!(atomic.take().is_none());
+    register(&atomic, unpark_panic());
+    assert!(atomic.take().is_some());
+    atomic.notify();
+}
+
+struct Waiter {
+    flag: Arc<(AtomicBool, AtomicTask)>,
+}
+
+impl Future for Waiter {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        if self.flag.0.swap(false, Ordering::SeqCst) {
+            return Ok(Async::Ready(()))
+        }
+        self.flag.1.register();
+        if self.flag.0.swap(false, Ordering::SeqCst) {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+#[test]
+fn concurrent_notify() {
+    const N: usize = 10000;
+
+    let flag = Arc::new((AtomicBool::new(false), AtomicTask::new()));
+    let done = Arc::new(AtomicBool::new(false));
+    let notifier = {
+        let flag = flag.clone();
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                flag.0.store(true, Ordering::SeqCst);
+                flag.1.notify();
+                thread::yield_now();
+            }
+        })
+    };
+
+    // Each wait must complete, which wouldn't be the case if a notification
+    // was lost while the task was being registered.
+    for _ in 0..N {
+        Waiter { flag: flag.clone() }.wait().unwrap();
+    }
+    done.store(true, Ordering::SeqCst);
+    notifier.join().unwrap();
+}
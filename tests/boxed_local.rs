@@ -0,0 +1,129 @@
+extern crate futures;
+
+use std::rc::Rc;
+
+use futures::{Future, LocalBoxFuture, finished, failed};
+use futures::stream::{self, Stream, LocalBoxStream};
+
+mod support;
+use support::*;
+
+fn rc_future(i: i32) -> LocalBoxFuture<Rc<i32>, u32> {
+    finished(Rc::new(i)).boxed_local()
+}
+
+fn rc_stream(items: Vec<i32>) -> LocalBoxStream<Rc<i32>, u32> {
+    let items = items.into_iter().map(|i| Ok(Rc::new(i))).collect::<Vec<_>>();
+    stream::iter(items).boxed_local()
+}
+
+fn get(rc: Rc<i32>) -> i32 {
+    *rc
+}
+
+#[test]
+fn future_combinators() {
+    // Closures capturing an `Rc` can't be used with `boxed`.
+    let rc = Rc::new(10);
+    assert_done(|| {
+        let rc = rc.clone();
+        rc_future(1).map(get).map(move |i| i + *rc).boxed_local()
+    }, Ok(11));
+
+    assert_done(|| rc_future(1).map_err(|e| e + 1).map(get), Ok(1));
+    assert_done(|| rc_future(1).then(|r| r.map(get)), Ok(1));
+    assert_done(|| rc_future(1).and_then(|_| rc_future(2)).map(get), Ok(2));
+    assert_done(|| {
+        failed::<Rc<i32>, u32>(1).boxed_local()
+                                  .or_else(|_| rc_future(3))
+                                  .map(get)
+    }, Ok(3));
+    assert_done(|| rc_future(1).join(rc_future(2)).map(|(a, b)| *a + *b),
+                Ok(3));
+    assert_done(|| {
+        rc_future(1).select(rc_future(2)).map(|(a, _)| *a).map_err(|(e, _)| e)
+    }, Ok(1));
+    assert_done(|| rc_future(1).fuse().map(get), Ok(1));
+    assert_done(|| {
+        finished::<_, u32>(rc_future(4)).boxed_local().flatten().map(get)
+    }, Ok(4));
+    assert_done(|| {
+        rc_stream(vec![5]).into_future().map(|(i, _)| *i.unwrap())
+                          .map_err(|(e, _)| e)
+    }, Ok(5));
+}
+
+#[test]
+fn future_forwarding() {
+    let mut boxed: Box<Future<Item = Rc<i32>, Error = u32>> =
+        Box::new(finished(Rc::new(1)));
+    assert_eq!((&mut boxed).map(get).wait(), Ok(1));
+
+    let mut local = rc_future(2);
+    assert_eq!((&mut local).and_then(|i| finished(*i)).wait(), Ok(2));
+    let mut local = rc_future(3);
+    assert_eq!(Box::new(&mut local).map(get).wait(), Ok(3));
+}
+
+#[test]
+fn stream_combinators() {
+    let rc = Rc::new(10);
+    assert_done(|| {
+        let rc = rc.clone();
+        rc_stream(vec![1, 2]).map(move |i| *i + *rc).boxed_local().collect()
+    }, Ok(vec![11, 12]));
+
+    assert_done(|| rc_stream(vec![1, 2, 3]).map(get).filter(|i| i % 2 == 1)
+                                           .collect(),
+                Ok(vec![1, 3]));
+    assert_done(|| {
+        rc_stream(vec![1, 2, 3]).filter_map(|i| Some(*i).filter(|&i| i > 1))
+                                .collect()
+    }, Ok(vec![2, 3]));
+    assert_done(|| rc_stream(vec![1, 2]).then(|r| r.map(get)).collect(),
+                Ok(vec![1, 2]));
+    assert_done(|| rc_stream(vec![1, 2]).and_then(|i| rc_future(*i + 1))
+                                        .map(get).collect(),
+                Ok(vec![2, 3]));
+    assert_done(|| rc_stream(vec![1, 2]).or_else(failed).map(get)
+                                        .collect(),
+                Ok(vec![1, 2]));
+    assert_done(|| {
+        rc_stream(vec![1, 2, 3]).fold(0, |a, i| finished::<i32, u32>(a + *i))
+    }, Ok(6));
+    assert_done(|| rc_stream(vec![1, 2]).for_each(|_| Ok(())), Ok(()));
+    assert_done(|| rc_stream(vec![1, 2, 3]).skip(1).take(1).map(get).collect(),
+                Ok(vec![2]));
+    assert_done(|| {
+        rc_stream(vec![1, 2]).zip(rc_stream(vec![3, 4]))
+                             .map(|(a, b)| *a + *b)
+                             .collect()
+    }, Ok(vec![4, 6]));
+    assert_done(|| {
+        rc_stream(vec![1]).merge(rc_stream(vec![2])).map(|_| ()).collect()
+    }, Ok(vec![()]));
+    assert_done(|| rc_stream(vec![1, 2]).peekable().fuse().map(get).collect(),
+                Ok(vec![1, 2]));
+    assert_done(|| {
+        let streams = vec![Ok::<_, u32>(rc_stream(vec![1])),
+                           Ok(rc_stream(vec![2]))];
+        stream::iter(streams).boxed_local().flatten().map(get).collect()
+    }, Ok(vec![1, 2]));
+    assert_done(|| {
+        let futures = vec![Ok::<_, u32>(rc_future(1)), Ok(rc_future(2))];
+        stream::iter(futures).boxed_local().buffered(2).map(get).collect()
+    }, Ok(vec![1, 2]));
+}
+
+#[test]
+fn stream_forwarding() {
+    let mut boxed: Box<Stream<Item = Rc<i32>, Error = u32>> =
+        Box::new(rc_stream(vec![1, 2, 3]));
+    assert_eq!((&mut boxed).take(1).map(get).collect().wait(), Ok(vec![1]));
+    assert_eq!((&mut boxed).map(get).collect().wait(), Ok(vec![2, 3]));
+
+    let mut local = rc_stream(vec![4, 5]);
+    assert_eq!(Box::new(&mut local).take(1).map(get).collect().wait(),
+               Ok(vec![4]));
+    assert_eq!((&mut local).map(get).collect().wait(), Ok(vec![5]));
+}
@@ -0,0 +1,135 @@
+extern crate futures;
+
+use std::sync::Arc;
+use std::thread;
+
+use futures::Async;
+use futures::task::{self, EventSet, UnparkEvent};
+use futures::task::{AtomicBitSet, GrowableBitSet, ReadyQueue};
+
+mod support;
+use support::*;
+
+// Inserts `0..n` into `set` from several threads at once.
+fn insert_concurrently<S: EventSet>(set: &Arc<S>, n: usize) {
+    let threads = (0..4).map(|t| {
+        let set = set.clone();
+        thread::spawn(move || {
+            for id in (0..n).filter(|id| id % 4 == t) {
+                set.insert(id);
+            }
+        })
+    }).collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap();
+    }
+}
+
+#[test]
+fn atomic_bit_set() {
+    let set = AtomicBitSet::new(130);
+    assert_eq!(set.capacity(), 130);
+    assert_eq!(set.drain().count(), 0);
+    for &id in [129, 0, 64, 63, 0].iter() {
+        set.insert(id);
+    }
+    assert_eq!(set.drain().collect::<Vec<_>>(), vec![0, 63, 64, 129]);
+    assert_eq!(set.drain().count(), 0);
+
+    // Ids which aren't reached by a drain stay in the set.
+    set.insert(1);
+    set.insert(100);
+    assert_eq!(set.drain().next(), Some(1));
+    assert_eq!(set.drain().collect::<Vec<_>>(), vec![100]);
+
+    let set = Arc::new(AtomicBitSet::new(1000));
+    insert_concurrently(&set, 1000);
+    assert_eq!(set.drain().collect::<Vec<_>>(), (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn growable_bit_set_insert_while_draining() {
+    let set = Arc::new(GrowableBitSet::new());
+    set.insert(0);
+    set.insert(5);
+    let mut drain = set.drain();
+    assert_eq!(drain.next(), Some(0));
+
+    // Neither growing the set on another thread nor inserting on the draining
+    // thread waits for the drain to finish.
+    let set2 = set.clone();
+    thread::spawn(move || set2.insert(100_000)).join().unwrap();
+    set.insert(1);
+    set.insert(200_000);
+    assert_eq!(drain.collect::<Vec<_>>(), vec![5, 100_000, 200_000]);
+    assert_eq!(set.drain().collect::<Vec<_>>(), vec![1]);
+}
+
+#[test]
+#[should_panic]
+fn atomic_bit_set_out_of_range() {
+    AtomicBitSet::new(10).insert(10);
+}
+
+#[test]
+fn growable_bit_set() {
+    let set = GrowableBitSet::new();
+    assert_eq!(set.drain().count(), 0);
+    set.insert(1000);
+    set.insert(3);
+    set.insert(1000);
+    assert_eq!(set.drain().collect::<Vec<_>>(), vec![3, 1000]);
+    assert_eq!(set.drain().count(), 0);
+
+    let set = Arc::new(GrowableBitSet::new());
+    insert_concurrently(&set, 1000);
+    assert_eq!(set.drain().collect::<Vec<_>>(), (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn ready_queue() {
+    let queue = ReadyQueue::new();
+    assert_eq!(queue.drain().count(), 0);
+    for &id in [3, 1, 3, 2].iter() {
+        queue.insert(id);
+    }
+    let mut drain = queue.drain();
+    assert_eq!(drain.next(), Some(3));
+    queue.insert(4);
+    assert_eq!(drain.collect::<Vec<_>>(), vec![1, 3, 2]);
+    assert_eq!(queue.drain().collect::<Vec<_>>(), vec![4]);
+
+    // Undrained ids are freed along with the queue.
+    queue.insert(5);
+    drop(queue);
+
+    // Each thread's insertions are drained in order.
+    let queue = Arc::new(ReadyQueue::new());
+    insert_concurrently(&queue, 1000);
+    let ids = queue.drain().collect::<Vec<_>>();
+    assert_eq!(ids.len(), 1000);
+    for t in 0..4 {
+        let from_t = ids.iter().cloned().filter(|id| id % 4 == t);
+        assert!(from_t.clone().zip(from_t.skip(1)).all(|(a, b)| a < b));
+    }
+}
+
+#[test]
+fn with_unpark_event() {
+    let queue = Arc::new(ReadyQueue::new());
+    let queue2 = queue.clone();
+    let mut t = task::spawn(futures::lazy(move || {
+        let tasks = (0..3).map(|i| {
+            let event = UnparkEvent::new(queue2.clone(), i);
+            task::with_unpark_event(event, task::park)
+        }).collect::<Vec<_>>();
+        Ok::<_, ()>(tasks)
+    }));
+    let tasks = match t.poll_future(unpark_noop()) {
+        Ok(Async::Ready(tasks)) => tasks,
+        _ => panic!(),
+    };
+    tasks[2].unpark();
+    tasks[0].unpark();
+    assert_eq!(queue.drain().collect::<Vec<_>>(), vec![2, 0]);
+}
@@ -0,0 +1,102 @@
+extern crate futures;
+
+use std::sync::mpsc::channel;
+
+use futures::{Future, Poll, Async, oneshot, empty, finished, failed};
+use futures::task;
+
+mod support;
+use support::*;
+
+struct DropSignal<F> {
+    _tx: ::std::sync::mpsc::Sender<()>,
+    future: F,
+}
+
+impl<F: Future> Future for DropSignal<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<F::Item, F::Error> {
+        self.future.poll()
+    }
+}
+
+#[test]
+fn all_children_complete() {
+    let (handle, group) = task::group::<u32>();
+    let (c1, p1) = oneshot::<()>();
+    let (c2, p2) = oneshot::<()>();
+    handle.wrap(p1.map_err(|_| 1)).forget();
+    handle.wrap(p2.map_err(|_| 2)).forget();
+
+    let mut group = task::spawn(group);
+    assert!(group.poll_future(unpark_noop()).unwrap().is_not_ready());
+    c1.complete(());
+    c2.complete(());
+    drop(handle);
+    assert_eq!(group.wait_future(), Ok(()));
+}
+
+#[test]
+fn waits_for_handles() {
+    let (handle, group) = task::group::<u32>();
+    let mut group = task::spawn(group);
+    assert!(group.poll_future(unpark_noop()).unwrap().is_not_ready());
+
+    let handle2 = handle.clone();
+    drop(handle);
+    assert!(group.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(handle2.wrap(finished(())).wait(), Ok(()));
+    drop(handle2);
+    assert_eq!(group.poll_future(unpark_panic()), Ok(Async::Ready(())));
+}
+
+#[test]
+fn first_error_cancels_siblings() {
+    let (handle, group) = task::group::<u32>();
+    let (tx, rx) = channel::<()>();
+    let (c, p) = oneshot::<()>();
+
+    handle.wrap(DropSignal { _tx: tx, future: empty() }).forget();
+    handle.wrap(p.then(|_| failed(3))).forget();
+    assert!(!handle.is_canceled());
+
+    c.complete(());
+    assert_eq!(group.wait(), Err(3));
+    assert!(handle.is_canceled());
+    assert!(rx.recv().is_err());
+}
+
+#[test]
+fn drop_group_cancels_children() {
+    let (handle, group) = task::group::<u32>();
+    let (tx, rx) = channel::<()>();
+    handle.wrap(DropSignal { _tx: tx, future: empty() }).forget();
+
+    drop(group);
+    assert!(handle.is_canceled());
+    assert!(rx.recv().is_err());
+
+    // New children of a canceled group never run.
+    let child = handle.wrap(futures::lazy(|| -> Result<(), u32> {
+        panic!("should not be run")
+    }));
+    assert_eq!(child.wait(), Ok(()));
+}
+
+#[test]
+fn panic_propagates() {
+    let (handle, group) = task::group::<u32>();
+    let (tx, rx) = channel::<()>();
+    handle.wrap(DropSignal { _tx: tx, future: empty() }).forget();
+
+    let child = handle.wrap(futures::lazy(|| -> Result<(), u32> {
+        panic!("child")
+    }));
+    assert!(::std::thread::spawn(|| child.wait()).join().is_err());
+    assert!(rx.recv().is_err());
+
+    drop(handle);
+    assert!(::std::thread::spawn(|| group.wait()).join().is_err());
+}
@@ -0,0 +1,121 @@
+extern crate futures;
+extern crate log;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use futures::oneshot;
+use futures::task::{self, Instrument, Observer, TaskId};
+
+mod support;
+use support::*;
+
+struct Logger(Arc<Mutex<Vec<String>>>);
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::LogMetadata) -> bool {
+        metadata.level() <= log::LogLevel::Warn
+    }
+
+    fn log(&self, record: &log::LogRecord) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push(format!("{}", record.args()));
+        }
+    }
+}
+
+#[derive(Default)]
+struct Recorder {
+    polls: Mutex<Vec<(TaskId, Option<String>)>>,
+    unparks: Mutex<Vec<TaskId>>,
+}
+
+impl Observer for Recorder {
+    fn polled(&self, id: TaskId, name: Option<&str>, _elapsed: Duration) {
+        self.polls.lock().unwrap().push((id, name.map(|s| s.to_string())));
+    }
+
+    fn unparked(&self, id: TaskId, _name: Option<&str>) {
+        self.unparks.lock().unwrap().push(id);
+    }
+}
+
+// Instrumentation is configured globally, so everything lives in one test.
+#[test]
+fn instrumentation() {
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let logs2 = logs.clone();
+    log::set_logger(|max| {
+        max.set(log::LogLevelFilter::Warn);
+        Box::new(Logger(logs2))
+    }).unwrap();
+
+    // Tasks aren't instrumented by default.
+    let (c, p) = oneshot::<u32>();
+    let mut plain = task::spawn(p);
+    assert!(plain.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(plain.stats().is_none());
+    drop(c);
+
+    let recorder = Arc::new(Recorder::default());
+    task::set_instrumentation(Some(
+        Instrument::new()
+            .slow_poll_threshold(Duration::from_millis(20))
+            .observer(recorder.clone())
+    ));
+
+    let (c, p) = oneshot::<u32>();
+    let mut a = task::Builder::new().name("a".to_string()).spawn(p);
+    assert_eq!(a.stats().unwrap().polls(), 0);
+    assert!(a.poll_future(unpark_noop()).unwrap().is_not_ready());
+    c.complete(1);
+    assert_eq!(a.poll_future(unpark_panic()), Ok(futures::Async::Ready(1)));
+    let stats = a.stats().unwrap();
+    assert_eq!(stats.polls(), 2);
+    assert_eq!(stats.unparks(), 1);
+    assert_eq!(recorder.polls.lock().unwrap().len(), 2);
+    assert_eq!(recorder.polls.lock().unwrap()[0].1, Some("a".to_string()));
+    assert_eq!(recorder.unparks.lock().unwrap().len(), 1);
+    assert!(logs.lock().unwrap().is_empty());
+
+    let mut slow = task::Builder::new().name("slow".to_string()).spawn(
+        futures::lazy(|| {
+            thread::sleep(Duration::from_millis(50));
+            Ok::<(), ()>(())
+        })
+    );
+    slow.wait_future().unwrap();
+    let stats = slow.stats().unwrap();
+    assert!(stats.poll_time() >= Duration::from_millis(50));
+    {
+        let logs = logs.lock().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].contains("(slow)"), "{}", logs[0]);
+    }
+
+    // Stats show up in the registry as well.
+    task::enable_registry(true);
+    let b = task::spawn(futures::empty::<(), ()>());
+    assert_eq!(task::live_tasks()[0].stats(), b.stats());
+    assert!(task::dump_tasks().contains("0 polls"));
+    task::enable_registry(false);
+
+    // Replacing the configuration takes effect for tasks which are already
+    // instrumented, on any thread.
+    let recorder2 = Arc::new(Recorder::default());
+    task::set_instrumentation(Some(
+        Instrument::new().observer(recorder2.clone())
+    ));
+    let mut d = task::spawn(futures::empty::<(), ()>());
+    assert!(d.poll_future(unpark_noop()).unwrap().is_not_ready());
+    thread::spawn(move || {
+        assert!(d.poll_future(unpark_noop()).unwrap().is_not_ready());
+    }).join().unwrap();
+    assert_eq!(recorder2.polls.lock().unwrap().len(), 2);
+    assert_eq!(recorder.polls.lock().unwrap().len(), 3);
+
+    task::set_instrumentation(None);
+    let c = task::spawn(futures::empty::<(), ()>());
+    assert!(c.stats().is_none());
+}
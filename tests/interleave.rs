@@ -0,0 +1,184 @@
+extern crate futures;
+
+use futures::{Future, Poll, Async, finished, failed};
+use futures::stream::{self, Stream};
+
+mod support;
+use support::*;
+
+fn numbers(seed: u64) -> stream::BoxStream<i32, u32> {
+    interleave_pending_stream(stream::iter((0..10).map(Ok)), seed).boxed()
+}
+
+fn with_err(seed: u64) -> stream::BoxStream<i32, u32> {
+    let items = vec![Ok(1), Ok(2), Err(3), Ok(4)];
+    interleave_pending_stream(stream::iter(items), seed).boxed()
+}
+
+#[test]
+fn futures() {
+    assert_interleaved(|seed| {
+        interleave_pending(f_ok(1), seed)
+            .join(interleave_pending(f_ok(2), seed + 100))
+    });
+    assert_interleaved(|seed| {
+        interleave_pending(f_ok(1), seed)
+            .select(interleave_pending(f_ok(1), seed + 100))
+            .map(|(a, _)| a)
+            .map_err(|(e, _)| e)
+    });
+    assert_interleaved(|seed| {
+        interleave_pending(f_ok(1), seed).and_then(move |a| {
+            interleave_pending(f_ok(a + 1), seed + 100)
+        })
+    });
+    assert_interleaved(|seed| {
+        interleave_pending(f_err(1), seed).or_else(move |_| {
+            interleave_pending(f_err(2), seed + 100)
+        })
+    });
+    assert_interleaved(|seed| {
+        interleave_pending(f_err(1), seed)
+            .join(interleave_pending(f_ok(2), seed + 100))
+    });
+}
+
+#[test]
+fn map_filter() {
+    assert_interleaved(|seed| {
+        numbers(seed).map(|a| a * 2).filter(|a| a % 3 == 0).collect()
+    });
+    assert_interleaved(|seed| {
+        numbers(seed).filter_map(|a| if a > 5 { Some(a) } else { None })
+                     .collect()
+    });
+    assert_interleaved(|seed| with_err(seed).map(|a| a + 1).collect());
+}
+
+#[test]
+fn and_then_then() {
+    assert_interleaved(|seed| {
+        numbers(seed).and_then(move |a| {
+            interleave_pending(finished(a + 1), seed + a as u64)
+        }).collect()
+    });
+    assert_interleaved(|seed| {
+        numbers(seed).then(move |a| {
+            interleave_pending(failed::<i32, u32>(a.unwrap() as u32),
+                               seed + 100)
+        }).collect()
+    });
+    assert_interleaved(|seed| {
+        with_err(seed).or_else(move |e| {
+            interleave_pending(finished::<i32, u32>(e as i32), seed + 100)
+        }).collect()
+    });
+}
+
+#[test]
+fn fold_for_each() {
+    assert_interleaved(|seed| {
+        numbers(seed).fold(0, move |a, b| {
+            interleave_pending(finished::<i32, u32>(a + b), seed + b as u64)
+        })
+    });
+    assert_interleaved(|seed| {
+        with_err(seed).fold(0, |a, b| finished::<i32, u32>(a + b))
+    });
+    assert_interleaved(|seed| numbers(seed).for_each(|_| Ok(())));
+}
+
+#[test]
+fn take_skip() {
+    assert_interleaved(|seed| numbers(seed).take(3).collect());
+    assert_interleaved(|seed| numbers(seed).skip(3).collect());
+    assert_interleaved(|seed| {
+        numbers(seed).skip_while(move |a| {
+            interleave_pending(finished(*a < 4), seed + 100)
+        }).collect()
+    });
+    assert_interleaved(|seed| with_err(seed).skip(1).collect());
+    assert_interleaved(|seed| {
+        numbers(seed).take_while(move |a| {
+            interleave_pending(finished(*a < 4), seed + 100)
+        }).collect()
+    });
+    assert_interleaved(|seed| {
+        numbers(seed).scan(0, move |sum, a| {
+            *sum += a;
+            interleave_pending(finished(Some(*sum).filter(|&s| s < 20)),
+                               seed + 100)
+        }).collect()
+    });
+    assert_interleaved(|seed| {
+        let until = interleave_pending(futures::empty::<(), u32>(), seed + 100);
+        numbers(seed).take_until(until).enumerate().collect()
+    });
+}
+
+#[test]
+fn zip_merge() {
+    assert_interleaved(|seed| numbers(seed).zip(numbers(seed + 100)).collect());
+    assert_interleaved(|seed| numbers(seed).zip(with_err(seed + 100)).collect());
+    assert_interleaved(|seed| {
+        numbers(seed).merge(numbers(seed + 100)).fold((0, 0), |(a, b), item| {
+            let pair = match item {
+                stream::MergedItem::First(x) => (a + x, b),
+                stream::MergedItem::Second(y) => (a, b + y),
+                stream::MergedItem::Both(x, y) => (a + x, b + y),
+            };
+            Ok::<_, u32>(pair)
+        })
+    });
+}
+
+#[test]
+fn flatten() {
+    assert_interleaved(|seed| {
+        numbers(seed).map(move |a| numbers(seed + a as u64).take(a as u64))
+                     .flatten()
+                     .collect()
+    });
+    assert_interleaved(|seed| {
+        numbers(seed).map(move |a| with_err(seed + a as u64)).flatten().collect()
+    });
+}
+
+struct PeekFirst<S: Stream>(Option<stream::Peekable<S>>);
+
+impl<S: Stream> Future for PeekFirst<S> where S::Item: Clone {
+    type Item = (Option<S::Item>, stream::Peekable<S>);
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, S::Error> {
+        let first = match try!(self.0.as_mut().unwrap().peek()) {
+            Async::Ready(item) => item.cloned(),
+            Async::NotReady => return Ok(Async::NotReady),
+        };
+        Ok(Async::Ready((first, self.0.take().unwrap())))
+    }
+}
+
+#[test]
+fn peekable() {
+    assert_interleaved(|seed| {
+        PeekFirst(Some(numbers(seed).peekable())).and_then(|(first, s)| {
+            s.collect().map(move |v| (first, v))
+        })
+    });
+}
+
+#[test]
+fn buffered() {
+    assert_interleaved(|seed| {
+        numbers(seed).map(move |a| interleave_pending(f_ok(a), seed + a as u64))
+                     .buffered(3)
+                     .collect()
+    });
+    assert_interleaved(|seed| {
+        numbers(seed).map(move |a| interleave_pending(f_ok(a), seed + a as u64))
+                     .buffer_unordered(3)
+                     .collect()
+                     .map(|mut v| { v.sort(); v })
+    });
+}
@@ -0,0 +1,100 @@
+extern crate futures;
+
+use std::thread;
+
+use futures::{Future, Poll, Async, oneshot};
+use futures::stream::{self, Stream};
+use futures::task::{self, WakeupCheck};
+
+mod support;
+use support::*;
+
+struct Forgetful;
+
+impl Future for Forgetful {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        Ok(Async::NotReady)
+    }
+}
+
+impl Stream for Forgetful {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<()>, ()> {
+        Ok(Async::NotReady)
+    }
+}
+
+#[test]
+fn off_by_default() {
+    let mut task = task::spawn(Forgetful);
+    assert!(task.poll_future(unpark_noop()).unwrap().is_not_ready());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn panics_on_lost_wakeup() {
+    let res = thread::spawn(|| {
+        let mut task = task::spawn(Forgetful);
+        task.check_wakeups(WakeupCheck::Panic);
+        let _ = task.poll_future(unpark_noop());
+    }).join();
+    assert!(res.is_err());
+
+    let res = thread::spawn(|| {
+        let mut task = task::spawn(Forgetful);
+        task.check_wakeups(WakeupCheck::Panic);
+        let _ = task.poll_stream(unpark_noop());
+    }).join();
+    assert!(res.is_err());
+}
+
+#[test]
+fn parked_tasks_pass() {
+    let (c, p) = oneshot::<u32>();
+    let mut task = task::spawn(p.map(|_| ()).map_err(|_| ()));
+    task.check_wakeups(WakeupCheck::Panic);
+    assert!(task.poll_future(unpark_noop()).unwrap().is_not_ready());
+    c.complete(1);
+    assert_eq!(task.poll_future(unpark_noop()), Ok(Async::Ready(())));
+
+    let (tx, rx) = stream::channel::<u32, ()>();
+    let mut task = task::spawn(rx);
+    task.check_wakeups(WakeupCheck::Panic);
+    assert!(task.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    drop(tx);
+    assert_eq!(task.poll_stream(unpark_noop()), Ok(Async::Ready(None)));
+}
+
+#[test]
+fn log_does_not_panic() {
+    let mut task = task::spawn(Forgetful);
+    task.check_wakeups(WakeupCheck::Log);
+    assert!(task.poll_future(unpark_noop()).unwrap().is_not_ready());
+}
+
+#[test]
+fn nested_spawn_tracked_separately() {
+    struct Outer(task::Spawn<Forgetful>);
+
+    impl Future for Outer {
+        type Item = ();
+        type Error = ();
+
+        fn poll(&mut self) -> Poll<(), ()> {
+            // The outer task parks, but the inner one doesn't.
+            let _ = task::park();
+            self.0.poll_future(unpark_noop())
+        }
+    }
+
+    let mut inner = task::spawn(Forgetful);
+    inner.check_wakeups(WakeupCheck::Off);
+    let mut outer = task::spawn(Outer(inner));
+    outer.check_wakeups(WakeupCheck::Panic);
+    assert!(outer.poll_future(unpark_noop()).unwrap().is_not_ready());
+}
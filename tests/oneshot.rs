@@ -3,7 +3,7 @@ extern crate futures;
 use std::sync::mpsc::{channel, Sender};
 use std::thread;
 
-use futures::{oneshot, Complete, Future, Poll};
+use futures::{oneshot, Async, Canceled, Complete, Future, Poll};
 
 mod support;
 use support::*;
@@ -66,3 +66,53 @@ fn cancel_lots() {
 
     t.join().unwrap();
 }
+
+#[test]
+fn send_returns_value() {
+    let (c, p) = oneshot::<u32>();
+    assert!(!c.is_canceled());
+    assert_eq!(c.send(1), Ok(()));
+    assert_eq!(p.wait(), Ok(1));
+
+    let (c, p) = oneshot::<u32>();
+    drop(p);
+    assert!(c.is_canceled());
+    assert_eq!(c.send(2), Err(2));
+}
+
+#[test]
+fn try_recv() {
+    let (c, mut p) = oneshot::<u32>();
+    assert_eq!(p.try_recv(), Ok(None));
+    assert_eq!(p.try_recv(), Ok(None));
+    c.complete(1);
+    assert_eq!(p.try_recv(), Ok(Some(1)));
+
+    let (c, mut p) = oneshot::<u32>();
+    drop(c);
+    assert_eq!(p.try_recv(), Err(Canceled));
+}
+
+#[test]
+fn close() {
+    let (mut c, mut p) = oneshot::<u32>();
+    let mut task = futures::task::spawn(futures::lazy(|| {
+        assert!(c.poll_cancel().unwrap().is_not_ready());
+        p.close();
+        assert!(c.is_canceled());
+        assert!(c.poll_cancel().unwrap().is_ready());
+        futures::finished::<_, ()>((c, p))
+    }));
+    let (c, p) = match task.poll_future(unpark_noop()) {
+        Ok(Async::Ready(pair)) => pair,
+        _ => panic!(),
+    };
+    assert_eq!(c.send(1), Err(1));
+    assert_eq!(p.wait(), Err(Canceled));
+
+    // A value sent before closing can still be received.
+    let (c, mut p) = oneshot::<u32>();
+    c.complete(2);
+    p.close();
+    assert_eq!(p.wait(), Ok(2));
+}
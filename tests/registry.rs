@@ -0,0 +1,104 @@
+extern crate futures;
+
+use std::sync::Arc;
+
+use futures::{Future, oneshot, empty};
+use futures::stream;
+use futures::task::{self, TaskState, Executor, Run};
+
+mod support;
+use support::*;
+
+struct Inline;
+
+impl Executor for Inline {
+    fn execute(&self, r: Run) {
+        r.run()
+    }
+}
+
+fn names_and_metadata() {
+    let mut task = task::Builder::new()
+        .name("named".to_string())
+        .metadata("k", "v")
+        .spawn(futures::lazy(|| {
+            let task = task::park();
+            assert_eq!(task.name(), Some("named"));
+            let debug = format!("{:?}", task);
+            assert!(debug.contains("named"), "{}", debug);
+            assert!(debug.contains("\"k\""), "{}", debug);
+            Ok::<(), ()>(())
+        }));
+    task.wait_future().unwrap();
+
+    let mut task = task::spawn(futures::lazy(|| {
+        assert_eq!(task::park().name(), None);
+        Ok::<(), ()>(())
+    }));
+    task.wait_future().unwrap();
+}
+
+// The registry is global, so everything spawning tasks lives in one test.
+#[test]
+fn registry() {
+    names_and_metadata();
+
+    // Tasks aren't recorded by default.
+    let _unrecorded = task::spawn(empty::<(), ()>());
+    assert!(task::live_tasks().is_empty());
+
+    task::enable_registry(true);
+
+    let (c, p) = oneshot::<u32>();
+    let mut a = task::Builder::new()
+        .name("a".to_string())
+        .metadata("shard", "1")
+        .spawn(p);
+    let mut b = task::spawn(stream::iter(vec![Ok::<u32, ()>(1)]));
+
+    let tasks = task::live_tasks();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].name(), Some("a"));
+    assert_eq!(tasks[0].metadata(), &[("shard".to_string(), "1".to_string())]);
+    assert_eq!(tasks[0].state(), TaskState::Idle);
+    assert!(tasks[0].since_last_poll().is_none());
+    assert_eq!(tasks[1].name(), None);
+    assert!(tasks[0].id() != tasks[1].id());
+
+    assert!(a.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(b.poll_stream(unpark_panic()).unwrap().is_ready());
+    let tasks = task::live_tasks();
+    assert_eq!(tasks[0].state(), TaskState::Parked);
+    assert!(tasks[0].since_last_poll().is_some());
+    assert_eq!(tasks[1].state(), TaskState::Idle);
+
+    let dump = task::dump_tasks();
+    assert_eq!(dump.lines().count(), 2);
+    assert!(dump.contains("(a): Parked"), "{}", dump);
+    assert!(dump.contains("shard=1"), "{}", dump);
+
+    c.complete(2);
+    assert!(a.poll_future(unpark_panic()).unwrap().is_ready());
+    assert!(b.poll_stream(unpark_panic()).unwrap().is_ready());
+    let tasks = task::live_tasks();
+    assert_eq!(tasks[0].state(), TaskState::Completed);
+    assert_eq!(tasks[1].state(), TaskState::Completed);
+
+    // Tasks are removed from the registry once dropped, including those
+    // which have been moved to an executor.
+    drop(a);
+    assert_eq!(task::live_tasks().len(), 1);
+    drop(b);
+    assert!(task::live_tasks().is_empty());
+
+    let (c, p) = oneshot::<()>();
+    task::spawn(p.map_err(|_| ())).execute(Arc::new(Inline));
+    assert_eq!(task::live_tasks().len(), 1);
+    assert_eq!(task::live_tasks()[0].state(), TaskState::Parked);
+    c.complete(());
+    assert!(task::live_tasks().is_empty());
+
+    task::enable_registry(false);
+    let _b = task::spawn(empty::<(), ()>());
+    assert!(task::live_tasks().is_empty());
+}
@@ -0,0 +1,107 @@
+extern crate futures;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+
+use futures::{Future, oneshot};
+use futures::stream::{self, Stream};
+use futures::task::{Simulation, Schedule, SimError};
+
+// A producer sending `n` items over a channel and a consumer collecting them,
+// along with a bystander task which records when it ran.
+fn spawn_pipeline(sim: &mut Simulation, log: Rc<RefCell<Vec<u32>>>) {
+    let (tx, rx) = stream::channel::<u32, ()>();
+    let (done_tx, done_rx) = oneshot::<()>();
+
+    fn send(n: u32, tx: stream::Sender<u32, ()>)
+            -> Box<Future<Item=(), Error=()>> {
+        if n == 0 {
+            return Box::new(futures::finished(()))
+        }
+        Box::new(tx.send(Ok(n)).map_err(|_| ()).and_then(move |tx| {
+            send(n - 1, tx)
+        }))
+    }
+
+    sim.spawn(send(5, tx));
+    let log2 = log.clone();
+    sim.spawn(rx.for_each(move |i| {
+        log2.borrow_mut().push(i);
+        Ok(())
+    }).then(move |_| {
+        done_tx.complete(());
+        Ok(())
+    }));
+    sim.spawn(done_rx.map(move |()| log.borrow_mut().push(0)).map_err(|_| ()));
+}
+
+#[test]
+fn runs_to_completion() {
+    for seed in 0..50 {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut sim = Simulation::new(seed);
+        spawn_pipeline(&mut sim, log.clone());
+        assert_eq!(sim.run(), Ok(()));
+        assert_eq!(*log.borrow(), [5, 4, 3, 2, 1, 0]);
+    }
+}
+
+#[test]
+fn same_seed_same_schedule() {
+    let schedule = |seed| {
+        let mut sim = Simulation::new(seed);
+        spawn_pipeline(&mut sim, Rc::new(RefCell::new(Vec::new())));
+        sim.run().unwrap();
+        sim.schedule().clone()
+    };
+    assert_eq!(schedule(3), schedule(3));
+    assert!((0..20).any(|seed| schedule(seed) != schedule(3)));
+}
+
+#[test]
+fn replay() {
+    let mut sim = Simulation::new(7);
+    spawn_pipeline(&mut sim, Rc::new(RefCell::new(Vec::new())));
+    sim.run().unwrap();
+    let schedule = sim.schedule().clone();
+
+    let mut sim = Simulation::replay(schedule.clone());
+    spawn_pipeline(&mut sim, Rc::new(RefCell::new(Vec::new())));
+    sim.run().unwrap();
+    assert_eq!(*sim.schedule(), schedule);
+}
+
+#[test]
+fn replay_diverges() {
+    let mut sim = Simulation::replay(Schedule::new(vec![1, 1]));
+    sim.spawn(futures::finished(()));
+    sim.spawn(futures::finished(()));
+    assert_eq!(sim.run(), Err(SimError::Diverged(1)));
+}
+
+#[test]
+fn deadlock() {
+    let (tx1, rx1) = oneshot::<()>();
+    let (tx2, rx2) = oneshot::<()>();
+
+    let mut sim = Simulation::new(0);
+    sim.spawn(futures::finished(()));
+    sim.spawn(rx1.map(move |()| tx2.complete(())).map_err(|_| ()));
+    sim.spawn(rx2.map(move |()| tx1.complete(())).map_err(|_| ()));
+    assert_eq!(sim.run(), Err(SimError::Deadlock(vec![1, 2])));
+}
+
+#[test]
+fn unparked_from_another_thread() {
+    let (tx, rx) = oneshot::<u32>();
+    let mut sim = Simulation::new(0);
+    sim.spawn(rx.map(|v| assert_eq!(v, 3)).map_err(|_| ()));
+
+    // The simulation doesn't wait for other threads, but picks up where it
+    // left off once they've unparked its tasks.
+    assert_eq!(sim.run(), Err(SimError::Deadlock(vec![0])));
+    thread::spawn(move || tx.complete(3)).join().unwrap();
+    assert_eq!(sim.run(), Ok(()));
+    assert_eq!(sim.schedule().steps(), &[0, 0]);
+}
@@ -1,8 +1,14 @@
 #[macro_use]
 extern crate futures;
 
-use futures::{failed, finished, Future, oneshot, Poll};
+use futures::{failed, finished, Async, Future, oneshot, Poll};
 use futures::stream::*;
+use futures::task;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::thread;
 
 mod support;
 use support::*;
@@ -238,3 +244,635 @@ fn wait() {
     assert_eq!(list().wait().collect::<Result<Vec<_>, _>>(),
                Ok(vec![1, 2, 3]));
 }
+
+#[test]
+fn chunks() {
+    assert_done(|| list().chunks(2).collect(), Ok(vec![vec![1, 2], vec![3]]));
+    assert_done(|| list().chunks(3).collect(), Ok(vec![vec![1, 2, 3]]));
+    assert_done(|| list().chunks(5).collect(), Ok(vec![vec![1, 2, 3]]));
+
+    // Items received before an error are yielded before it.
+    let mut s = iter(vec![ok(1), ok(2), err(3)]).chunks(5);
+    sassert_next(&mut s, vec![1, 2]);
+    sassert_err(&mut s, 3);
+    sassert_done(&mut s);
+}
+
+#[test]
+#[should_panic]
+fn chunks_zero() {
+    drop(list().chunks(0));
+}
+
+// A stream yielding `Some` items, and which isn't ready once for each `None`.
+struct Steps(Vec<Option<i32>>);
+
+impl Stream for Steps {
+    type Item = i32;
+    type Error = u32;
+
+    fn poll(&mut self) -> Poll<Option<i32>, u32> {
+        if self.0.is_empty() {
+            return Ok(None.into())
+        }
+        match self.0.remove(0) {
+            Some(i) => Ok(Some(i).into()),
+            None => {
+                futures::task::park().unpark();
+                Ok(futures::Async::NotReady)
+            }
+        }
+    }
+}
+
+#[test]
+fn ready_chunks() {
+    let steps = vec![Some(1), Some(2), None, Some(3), None, None,
+                     Some(4), Some(5), Some(6), Some(7)];
+    assert_done(|| Steps(steps.clone()).ready_chunks(3).collect(),
+                Ok(vec![vec![1, 2], vec![3], vec![4, 5, 6], vec![7]]));
+
+    let mut s = Steps(vec![None]).ready_chunks(3);
+    sassert_empty(&mut s);
+    sassert_done(&mut s);
+
+    let mut s = iter(vec![ok(1), ok(2), err(3)]).ready_chunks(5);
+    sassert_next(&mut s, vec![1, 2]);
+    sassert_err(&mut s, 3);
+    sassert_done(&mut s);
+}
+
+#[test]
+fn take_while() {
+    assert_done(|| list().take_while(|a| finished(*a < 3)).collect(),
+                Ok(vec![1, 2]));
+    assert_done(|| list().take_while(|_| finished(true)).collect(),
+                Ok(vec![1, 2, 3]));
+    assert_done(|| {
+        list().take_while(|a| if *a == 2 { Err(5) } else { Ok(true) })
+              .collect()
+    }, Err(5));
+
+    // Nothing is taken after the predicate fails once.
+    let mut s = iter(vec![ok(1), ok(5), ok(2)]).take_while(|a| Ok(*a < 3));
+    sassert_next(&mut s, 1);
+    sassert_done(&mut s);
+    sassert_done(&mut s);
+}
+
+#[test]
+fn take_until() {
+    let (c, p) = oneshot::<()>();
+    let mut s = list().take_until(p.map_err(|_| 10));
+    assert_eq!((&mut s).take(2).collect().wait(), Ok(vec![1, 2]));
+    c.complete(());
+    sassert_done(&mut s);
+    sassert_done(&mut s);
+
+    let (c, p) = oneshot::<()>();
+    let mut s = list().take_until(p.map_err(|_| 10));
+    drop(c);
+    sassert_err(&mut s, 10);
+    // The future isn't polled again after failing.
+    sassert_done(&mut s);
+
+    // The stream may also end before the future resolves.
+    let (_c, p) = oneshot::<()>();
+    assert_eq!(list().take_until(p.map_err(|_| 10)).collect().wait(),
+               Ok(vec![1, 2, 3]));
+}
+
+#[test]
+fn scan() {
+    assert_done(|| {
+        list().scan(10, |acc, a| {
+            *acc += a;
+            finished(Some(*acc))
+        }).collect()
+    }, Ok(vec![11, 13, 16]));
+    assert_done(|| {
+        list().scan((), |_, a| Ok(if a < 3 { Some(a * 2) } else { None }))
+              .collect()
+    }, Ok(vec![2, 4]));
+    assert_done(|| err_list().scan((), |_, a| finished(Some(a))).collect(),
+                Err(3));
+}
+
+#[test]
+fn inspect() {
+    let mut seen = Vec::new();
+    assert_eq!(list().inspect(|a| seen.push(*a)).collect().wait(),
+               Ok(vec![1, 2, 3]));
+    assert_eq!(seen, vec![1, 2, 3]);
+}
+
+#[test]
+fn enumerate() {
+    assert_done(|| list().enumerate().collect(),
+                Ok(vec![(0, 1), (1, 2), (2, 3)]));
+    assert_done(|| err_list().enumerate().collect(), Err(3));
+}
+
+#[test]
+fn select_all_fair() {
+    // Ready streams take turns, and the result ends with the last of them.
+    assert_done(|| {
+        select_all(vec![iter(vec![ok(1), ok(2), ok(3)]),
+                        iter(vec![ok(10), ok(20)]),
+                        iter(vec![])]).collect()
+    }, Ok(vec![1, 10, 2, 20, 3]));
+
+    let mut s = select_all(vec![iter(vec![ok(1), err(2), ok(3)])]);
+    assert_eq!(s.len(), 1);
+    sassert_next(&mut s, 1);
+    sassert_err(&mut s, 2);
+    s.push(iter(vec![ok(4)]));
+    assert_eq!(s.len(), 2);
+    sassert_next(&mut s, 3);
+    sassert_next(&mut s, 4);
+    sassert_done(&mut s);
+    assert!(s.is_empty());
+
+    let mut s = SelectAll::<Receiver<i32, u32>>::new();
+    sassert_done(&mut s);
+}
+
+// A stream which is never ready, counting how many times it's polled.
+struct Pending(Rc<Cell<usize>>);
+
+impl Stream for Pending {
+    type Item = i32;
+    type Error = u32;
+
+    fn poll(&mut self) -> Poll<Option<i32>, u32> {
+        self.0.set(self.0.get() + 1);
+        Ok(futures::Async::NotReady)
+    }
+}
+
+#[test]
+fn select_all_polls_unparked() {
+    let polls = Rc::new(Cell::new(0));
+    let (tx, rx) = channel();
+    let mut s = SelectAll::new();
+    s.push(Box::new(Pending(polls.clone())) as Box<Stream<Item=i32, Error=u32>>);
+    s.push(Box::new(rx));
+    sassert_empty(&mut s);
+    assert_eq!(polls.get(), 1);
+
+    let _tx = tx.send(Ok(5)).wait().ok().unwrap();
+    sassert_next(&mut s, 5);
+    sassert_empty(&mut s);
+    assert_eq!(polls.get(), 1);
+}
+
+#[test]
+fn merge_sorted() {
+    assert_done(|| {
+        let streams = vec![iter(vec![ok(1), ok(4), ok(7)]),
+                           iter(vec![]),
+                           iter(vec![ok(2), ok(3), ok(8), ok(9)]),
+                           iter(vec![ok(4), ok(5)])];
+        futures::stream::merge_sorted(streams, |a| *a).collect()
+    }, Ok(vec![1, 2, 3, 4, 4, 5, 7, 8, 9]));
+
+    // Equal keys come out in the order of the streams.
+    assert_done(|| {
+        let streams = vec![iter(vec![ok(10), ok(21)]),
+                           iter(vec![ok(11), ok(20)])];
+        futures::stream::merge_sorted(streams, |a| *a / 10).collect()
+    }, Ok(vec![10, 11, 21, 20]));
+
+    let streams = vec![iter(vec![ok(1), err(5), ok(4)]),
+                       iter(vec![ok(2), ok(3)])];
+    let mut s = futures::stream::merge_sorted(streams, |a| *a);
+    sassert_next(&mut s, 1);
+    sassert_err(&mut s, 5);
+    sassert_next(&mut s, 2);
+    sassert_next(&mut s, 3);
+    sassert_next(&mut s, 4);
+    sassert_done(&mut s);
+}
+
+#[test]
+fn merge_sorted_waits_for_all() {
+    let (tx, rx) = channel();
+    let streams = vec![Box::new(iter(vec![ok(2), ok(3)]))
+                           as Box<Stream<Item=i32, Error=u32>>,
+                       Box::new(rx)];
+    let mut s = futures::stream::merge_sorted(streams, |a| *a);
+    sassert_empty(&mut s);
+    let tx = tx.send(Ok(1)).wait().ok().unwrap();
+    sassert_next(&mut s, 1);
+    sassert_empty(&mut s);
+    drop(tx);
+    sassert_next(&mut s, 2);
+    sassert_next(&mut s, 3);
+    sassert_done(&mut s);
+}
+
+fn next_group<S: Stream>(s: &mut S) -> S::Item {
+    (&mut *s).wait().next().unwrap().ok().unwrap()
+}
+
+#[test]
+fn group_by() {
+    let mut groups = iter((1..8).map(ok)).group_by(10, |a| a % 3);
+    let mut subs = Vec::new();
+    for group in (&mut groups).wait() {
+        let (key, sub) = group.unwrap();
+        assert_eq!(*sub.key(), key);
+        subs.push(sub);
+    }
+    let items = subs.into_iter()
+                    .map(|sub| sub.collect().wait().unwrap())
+                    .collect::<Vec<_>>();
+    assert_eq!(items, vec![vec![1, 4, 7], vec![2, 5], vec![3, 6]]);
+
+    // Errors are yielded by the `GroupBy` stream.
+    let mut groups = iter(vec![ok(1), err(5), ok(3)]).group_by(10, |a| a % 2);
+    let (_, mut sub) = next_group(&mut groups);
+    sassert_err(&mut groups, 5);
+    sassert_done(&mut groups);
+    sassert_next(&mut sub, 1);
+    sassert_next(&mut sub, 3);
+    sassert_done(&mut sub);
+}
+
+#[test]
+fn group_by_back_pressure() {
+    let mut groups = iter((1..5).map(ok)).group_by(1, |a| a % 2);
+    let (_, mut odd) = next_group(&mut groups);
+    // The buffer of the odd group is full.
+    sassert_empty(&mut groups);
+    sassert_next(&mut odd, 1);
+    let (_, mut even) = next_group(&mut groups);
+    sassert_empty(&mut odd);
+    sassert_next(&mut even, 2);
+    sassert_next(&mut odd, 3);
+
+    // Items of a dropped group are discarded.
+    drop(odd);
+    sassert_next(&mut even, 4);
+    sassert_done(&mut even);
+    sassert_done(&mut groups);
+}
+
+#[test]
+fn group_by_threads() {
+    let groups = iter((0..1000).map(ok)).group_by(2, |a| a % 4);
+    let threads = groups.wait().map(|group| {
+        let (key, sub) = group.unwrap();
+        thread::spawn(move || (key, sub.collect().wait().unwrap()))
+    }).collect::<Vec<_>>();
+    for t in threads {
+        let (key, items) = t.join().unwrap();
+        assert_eq!(items, (0..1000).filter(|a| a % 4 == key)
+                                   .collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn group_by_panic() {
+    let steps = vec![Step::Item(1), Step::NotReady, Step::NotReady,
+                     Step::Item(3)];
+    let mut groups = mock_stream::<i32, u32, _>(steps).group_by(2, |a| {
+        assert!(*a != 3, "bad item");
+        a % 2
+    });
+    let (_, sub) = next_group(&mut groups);
+    let counter = CountUnpark::new();
+    let mut sub = task::spawn(sub);
+    assert_eq!(sub.poll_stream(counter.clone()), Ok(Async::Ready(Some(1))));
+    assert!(sub.poll_stream(counter.clone()).unwrap().is_not_ready());
+    let unparked = counter.count();
+
+    // A panic in the key function wakes the groups, which then end.
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| {
+        poll_stream_once(&mut groups)
+    })).is_err());
+    assert!(counter.count() > unparked);
+    assert_eq!(sub.poll_stream(unpark_panic()), Ok(Async::Ready(None)));
+    sassert_done(&mut groups);
+
+    // Dropping the groups while unwinding from the panic doesn't panic again.
+    let mut groups = iter(vec![ok(1), ok(3)]).group_by(1, |a| {
+        assert!(*a != 3, "bad item");
+        a % 2
+    });
+    let (_, sub) = next_group(&mut groups);
+    assert!(panic::catch_unwind(AssertUnwindSafe(move || {
+        let mut streams = (groups, sub);
+        sassert_next(&mut streams.1, 1);
+        poll_stream_once(&mut streams.1)
+    })).is_err());
+}
+
+#[test]
+fn fork() {
+    let mut forks = iter(vec![ok(1), err(2), ok(3)]).fork(3, 10);
+    for s in forks.iter_mut() {
+        sassert_next(s, 1);
+        sassert_err(s, 2);
+        sassert_next(s, 3);
+        sassert_done(s);
+    }
+
+    assert!(iter(vec![ok(1)]).fork(0, 1).is_empty());
+}
+
+#[test]
+fn fork_back_pressure() {
+    let polls = Rc::new(Cell::new(0));
+    let counted = {
+        let polls = polls.clone();
+        iter((1..10).map(ok)).inspect(move |_| polls.set(polls.get() + 1))
+    };
+    let mut forks = counted.fork(3, 2);
+    let mut c = forks.pop().unwrap();
+    let mut b = forks.pop().unwrap();
+    let mut a = forks.pop().unwrap();
+
+    // `b` and `c` hold back `a` once their buffers are full.
+    sassert_next(&mut a, 1);
+    sassert_next(&mut a, 2);
+    sassert_empty(&mut a);
+    assert_eq!(polls.get(), 2);
+    sassert_next(&mut b, 1);
+    sassert_empty(&mut a);
+    sassert_next(&mut c, 1);
+    sassert_next(&mut a, 3);
+    assert_eq!(polls.get(), 3);
+
+    // Dropped forks no longer hold back the others.
+    drop(b);
+    drop(c);
+    assert_eq!(a.collect().wait(), Ok((4..10).collect()));
+}
+
+#[test]
+fn fork_threads() {
+    let threads = iter((0..1000).map(ok)).fork(4, 3).into_iter().map(|s| {
+        thread::spawn(move || s.collect().wait().unwrap())
+    }).collect::<Vec<_>>();
+    for t in threads {
+        assert_eq!(t.join().unwrap(), (0..1000).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn fork_panic() {
+    let steps = vec![Step::NotReady, Step::Item(3)];
+    let stream = mock_stream::<i32, u32, _>(steps).map(|a| {
+        assert!(a != 3, "bad item");
+        a
+    });
+    let mut forks = stream.fork(2, 10);
+    let mut b = task::spawn(forks.pop().unwrap());
+    let mut a = forks.pop().unwrap();
+    let counter = CountUnpark::new();
+    assert!(b.poll_stream(counter.clone()).unwrap().is_not_ready());
+    let unparked = counter.count();
+
+    // A panic while polling the stream wakes the other forks, which then end.
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| {
+        poll_stream_once(&mut a)
+    })).is_err());
+    assert!(counter.count() > unparked);
+    assert_eq!(b.poll_stream(unpark_panic()), Ok(Async::Ready(None)));
+    sassert_done(&mut a);
+
+    // Dropping the forks while unwinding from the panic doesn't panic again.
+    let stream = iter(vec![ok(3)]).map(|a| {
+        assert!(a != 3, "bad item");
+        a
+    });
+    let mut forks = stream.fork(2, 10);
+    assert!(panic::catch_unwind(AssertUnwindSafe(move || {
+        poll_stream_once(&mut forks[0])
+    })).is_err());
+}
+
+#[test]
+fn partition() {
+    let (mut even, mut odd) = iter((1..6).map(ok))
+        .partition(3, |a| a % 2 == 0);
+    sassert_next(&mut odd, 1);
+    sassert_next(&mut odd, 3);
+    sassert_next(&mut even, 2);
+    sassert_next(&mut odd, 5);
+    sassert_done(&mut odd);
+    sassert_next(&mut even, 4);
+    sassert_done(&mut even);
+
+    // Errors are yielded by the stream which is being polled.
+    let (mut even, mut odd) = iter(vec![ok(1), ok(2), err(3), ok(4)])
+        .partition(3, |a| a % 2 == 0);
+    sassert_next(&mut odd, 1);
+    sassert_err(&mut odd, 3);
+    sassert_done(&mut odd);
+    sassert_next(&mut even, 2);
+    sassert_next(&mut even, 4);
+    sassert_done(&mut even);
+}
+
+#[test]
+fn partition_back_pressure() {
+    let (mut even, mut odd) = iter((1..10).map(ok))
+        .partition(2, |a| a % 2 == 0);
+    sassert_next(&mut even, 2);
+    // The buffer of `odd` is full.
+    sassert_empty(&mut even);
+    sassert_next(&mut odd, 1);
+    sassert_next(&mut even, 4);
+
+    // Items for a dropped stream are discarded.
+    drop(odd);
+    assert_eq!(even.collect().wait(), Ok(vec![6, 8]));
+}
+
+#[test]
+fn partition_panic() {
+    let steps = vec![Step::NotReady, Step::Item(3)];
+    let (even, mut odd) = mock_stream::<i32, u32, _>(steps).partition(2, |a| {
+        assert!(*a != 3, "bad item");
+        a % 2 == 0
+    });
+    let counter = CountUnpark::new();
+    let mut even = task::spawn(even);
+    assert!(even.poll_stream(counter.clone()).unwrap().is_not_ready());
+    let unparked = counter.count();
+
+    // A panic in the predicate wakes the other half, which then ends.
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| {
+        poll_stream_once(&mut odd)
+    })).is_err());
+    assert!(counter.count() > unparked);
+    assert_eq!(even.poll_stream(unpark_panic()), Ok(Async::Ready(None)));
+    sassert_done(&mut odd);
+
+    // Dropping both halves while unwinding from the panic doesn't panic again.
+    let (even, odd) = iter(vec![ok(3)]).partition(2, |a| {
+        assert!(*a != 3, "bad item");
+        true
+    });
+    assert!(panic::catch_unwind(AssertUnwindSafe(move || {
+        let mut halves = (even, odd);
+        poll_stream_once(&mut halves.1)
+    })).is_err());
+}
+
+#[test]
+fn unzip() {
+    let pairs = iter(vec![Ok((1, 'a')), Ok((2, 'b')), Err(5), Ok((3, 'c'))]);
+    let (mut left, mut right) = pairs.unzip(5);
+    sassert_next(&mut right, 'a');
+    sassert_next(&mut right, 'b');
+    sassert_err(&mut right, 5);
+    sassert_next(&mut left, 1);
+    sassert_next(&mut left, 2);
+    sassert_next(&mut left, 3);
+    sassert_done(&mut left);
+    sassert_next(&mut right, 'c');
+    sassert_done(&mut right);
+}
+
+#[test]
+fn unzip_threads() {
+    let pairs = iter((0..1000).map(|i| Ok::<_, ()>((i, i * 2))));
+    let (left, right) = pairs.unzip(2);
+    let left = thread::spawn(move || left.collect().wait().unwrap());
+    let right = thread::spawn(move || right.collect().wait().unwrap());
+    assert_eq!(left.join().unwrap(), (0..1000).collect::<Vec<_>>());
+    assert_eq!(right.join().unwrap(),
+               (0..1000).map(|i| i * 2).collect::<Vec<_>>());
+}
+
+#[test]
+fn collect_into() {
+    let map = iter(vec![ok(1), ok(2)]).map(|a| (a, a * 10))
+                                      .collect_into::<HashMap<_, _>>()
+                                      .wait()
+                                      .unwrap();
+    assert_eq!(map[&2], 20);
+    assert_done(|| {
+        iter(vec![Ok::<_, u32>('a'), Ok('b')]).collect_into::<String>()
+    }, Ok("ab".to_string()));
+    assert_done(|| err_list().collect_into::<HashSet<_>>(), Err(3));
+}
+
+#[test]
+fn concat() {
+    assert_done(|| list().map(|a| vec![a; a as usize]).concat(),
+                Ok(vec![1, 2, 2, 3, 3, 3]));
+    assert_done(|| iter(vec![Ok::<_, u32>(b"ab".to_vec()),
+                             Ok(b"c".to_vec())]).concat(),
+                Ok(b"abc".to_vec()));
+    assert_done(|| iter(Vec::<Result<Vec<i32>, u32>>::new()).concat(),
+                Ok(vec![]));
+    assert_done(|| err_list().map(|a| vec![a]).concat(), Err(3));
+}
+
+#[test]
+fn count() {
+    assert_done(|| list().count(), Ok(3));
+    assert_done(|| err_list().count(), Err(3));
+}
+
+#[test]
+fn any_all() {
+    assert_done(|| list().any(|a| a == 2), Ok(true));
+    assert_done(|| list().any(|a| a > 3), Ok(false));
+    assert_done(|| err_list().any(|a| a > 3), Err(3));
+    assert_done(|| list().all(|a| a > 0), Ok(true));
+    assert_done(|| list().all(|a| a < 2), Ok(false));
+    assert_done(|| err_list().all(|a| a > 0), Err(3));
+
+    // The rest of the stream isn't needed once the result is known.
+    let (tx, rx) = channel::<i32, u32>();
+    let tx = tx.send(Ok(1)).wait().ok().unwrap();
+    assert_eq!(rx.any(|a| a == 1).wait(), Ok(true));
+    drop(tx);
+}
+
+#[test]
+fn min_max_by() {
+    let items = || iter(vec![ok(3), ok(-1), ok(4), ok(1)]);
+    assert_done(|| items().min_by(|a, b| a.cmp(b)), Ok(Some(-1)));
+    assert_done(|| items().max_by(|a, b| a.cmp(b)), Ok(Some(4)));
+
+    // Ties go to the first item for `min_by` and the last for `max_by`.
+    let pairs = || iter(vec![Ok::<_, u32>((1, 'a')), Ok((0, 'b')),
+                             Ok((1, 'c')), Ok((0, 'd'))]);
+    assert_done(|| pairs().min_by(|a, b| a.0.cmp(&b.0)), Ok(Some((0, 'b'))));
+    assert_done(|| pairs().max_by(|a, b| a.0.cmp(&b.0)), Ok(Some((1, 'c'))));
+
+    assert_done(|| iter(Vec::<Result<i32, u32>>::new()).min_by(|a, b| a.cmp(b)),
+                Ok(None));
+    assert_done(|| err_list().max_by(|a, b| a.cmp(b)), Err(3));
+}
+
+#[test]
+fn for_each_concurrent() {
+    let pulled = Rc::new(Cell::new(0));
+    let pending = Rc::new(RefCell::new(Vec::new()));
+    let fut = {
+        let pulled = pulled.clone();
+        let pending = pending.clone();
+        iter((0..5).map(ok)).inspect(move |_| pulled.set(pulled.get() + 1))
+                            .for_each_concurrent(2, move |_| {
+            let (c, p) = oneshot::<()>();
+            pending.borrow_mut().push(c);
+            p.map_err(|_| 0)
+        })
+    };
+    let mut fut = futures::task::spawn(fut);
+    assert!(fut.poll_future(unpark_noop()).unwrap().is_not_ready());
+
+    // No more items are taken while the limit is reached.
+    assert_eq!(pulled.get(), 2);
+    assert!(fut.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(pulled.get(), 2);
+
+    let c = pending.borrow_mut().remove(0);
+    c.complete(());
+    assert!(fut.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(pulled.get(), 3);
+
+    loop {
+        let c = match pending.borrow_mut().pop() {
+            Some(c) => c,
+            None => break,
+        };
+        c.complete(());
+        if fut.poll_future(unpark_noop()).unwrap().is_ready() {
+            break
+        }
+    }
+    assert_eq!(pulled.get(), 5);
+    assert!(pending.borrow().is_empty());
+}
+
+#[test]
+fn for_each_concurrent_fails_fast() {
+    let pending = Rc::new(RefCell::new(Vec::new()));
+    let fut = {
+        let pending = pending.clone();
+        iter((0..5).map(ok)).for_each_concurrent(3, move |a| {
+            let (c, p) = oneshot::<()>();
+            pending.borrow_mut().push(c);
+            p.map_err(|_| 0).and_then(move |()| {
+                if a == 1 { Err(7) } else { Ok(()) }
+            })
+        })
+    };
+    let mut fut = futures::task::spawn(fut);
+    assert!(fut.poll_future(unpark_noop()).unwrap().is_not_ready());
+    pending.borrow_mut().remove(1).complete(());
+
+    // The futures still running are dropped along with the error.
+    assert_eq!(fut.poll_future(unpark_noop()), Err(7));
+    assert!(pending.borrow().iter().all(|c| c.is_canceled()));
+
+    assert_done(|| err_list().for_each_concurrent(2, |_| Ok(())), Err(3));
+}
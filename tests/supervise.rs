@@ -0,0 +1,123 @@
+extern crate futures;
+
+use std::time::Duration;
+
+use futures::*;
+use futures::stream::Stream;
+
+#[test]
+fn restarts_until_success() {
+    let mut attempts = 0;
+    let supervisor = supervise(RestartPolicy::always(), move || {
+        attempts += 1;
+        if attempts < 10 {
+            Err::<u32, u32>(attempts)
+        } else {
+            Ok(attempts)
+        }
+    });
+    assert_eq!(supervisor.wait().ok(), Some(10));
+}
+
+#[test]
+fn gives_up() {
+    let mut attempts = 0;
+    let mut supervisor = supervise(RestartPolicy::new(2), move || {
+        attempts += 1;
+        failed::<(), u32>(attempts)
+    });
+    let events = supervisor.events();
+    match supervisor.wait() {
+        Err(SupervisorError::Failed(3)) => {}
+        Err(e) => panic!("wrong error: {:?}", e),
+        Ok(()) => panic!("should have failed"),
+    }
+    assert_eq!(events.wait().map(|e| e.unwrap()).collect::<Vec<_>>(), vec![
+        SupervisorEvent::Started(0),
+        SupervisorEvent::Failed(1),
+        SupervisorEvent::Started(1),
+        SupervisorEvent::Failed(2),
+        SupervisorEvent::Started(2),
+        SupervisorEvent::GaveUp,
+    ]);
+}
+
+#[test]
+fn never_restarts() {
+    let supervisor = supervise(RestartPolicy::never(), || failed::<(), u32>(1));
+    assert!(supervisor.wait().is_err());
+}
+
+#[test]
+fn window_expires() {
+    let mut attempts = 0;
+    let policy = RestartPolicy::within(1, Duration::from_millis(0));
+    let supervisor = supervise(policy, move || {
+        attempts += 1;
+        if attempts < 5 {
+            Err::<u32, u32>(attempts)
+        } else {
+            Ok(attempts)
+        }
+    });
+    assert_eq!(supervisor.wait().ok(), Some(5));
+}
+
+#[test]
+fn restarts_on_panic() {
+    let mut attempts = 0;
+    let mut supervisor = supervise(RestartPolicy::new(1), move || {
+        attempts += 1;
+        let n = attempts;
+        lazy(move || {
+            if n == 1 {
+                panic!("boom");
+            }
+            finished::<u32, u32>(n)
+        })
+    });
+    let events = supervisor.events();
+    assert_eq!(supervisor.wait().ok(), Some(2));
+    assert_eq!(events.wait().map(|e| e.unwrap()).collect::<Vec<_>>(), vec![
+        SupervisorEvent::Started(0),
+        SupervisorEvent::Panicked("boom".to_string()),
+        SupervisorEvent::Started(1),
+        SupervisorEvent::Completed,
+    ]);
+}
+
+#[test]
+fn restarts_on_factory_panic() {
+    let mut attempts = 0;
+    let mut supervisor = supervise(RestartPolicy::new(1), move || {
+        attempts += 1;
+        if attempts == 1 {
+            panic!("no child");
+        }
+        finished::<u32, u32>(attempts)
+    });
+    let events = supervisor.events();
+    assert_eq!(supervisor.wait().ok(), Some(2));
+    assert_eq!(events.wait().map(|e| e.unwrap()).collect::<Vec<_>>(), vec![
+        SupervisorEvent::Panicked("no child".to_string()),
+        SupervisorEvent::Started(1),
+        SupervisorEvent::Completed,
+    ]);
+}
+
+#[test]
+fn events_end_on_drop() {
+    let mut supervisor = supervise(RestartPolicy::never(), empty::<(), u32>);
+    let events = supervisor.events();
+    let mut spawned = task::spawn(supervisor);
+    drop(spawned.poll_future(::std::sync::Arc::new(Noop)));
+    drop(spawned);
+    assert_eq!(events.wait().map(|e| e.unwrap()).collect::<Vec<_>>(), vec![
+        SupervisorEvent::Started(0),
+    ]);
+
+    struct Noop;
+    impl task::Unpark for Noop {
+        fn unpark(&self) {}
+    }
+}
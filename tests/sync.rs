@@ -0,0 +1,301 @@
+extern crate futures;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use futures::{Future, Async, Canceled, oneshot, finished, failed};
+use futures::sync::{Notify, Barrier, OnceCell};
+use futures::task::{self, Unpark};
+
+mod support;
+use support::*;
+
+struct Count(AtomicUsize);
+
+impl Count {
+    fn new() -> Arc<Count> {
+        Arc::new(Count(AtomicUsize::new(0)))
+    }
+
+    fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Unpark for Count {
+    fn unpark(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn notify_permits() {
+    let notify = Notify::new();
+    notify.notify_one();
+    notify.notify_one();
+    assert_done(|| notify.notified(), Ok(()));
+    assert_done(|| notify.notified(), Ok(()));
+    assert_empty(|| notify.notified());
+
+    // `notify_all` doesn't store a permit.
+    notify.notify_all();
+    assert_empty(|| notify.notified());
+}
+
+#[test]
+fn notify_one_wakes_oldest() {
+    let notify = Notify::new();
+    let (c1, c2) = (Count::new(), Count::new());
+    let mut a = task::spawn(notify.notified());
+    let mut b = task::spawn(notify.notified());
+    assert!(a.poll_future(c1.clone()).unwrap().is_not_ready());
+    assert!(b.poll_future(c2.clone()).unwrap().is_not_ready());
+
+    notify.notify_one();
+    assert_eq!((c1.get(), c2.get()), (1, 0));
+    assert!(b.poll_future(c2.clone()).unwrap().is_not_ready());
+    assert_eq!(a.poll_future(c1.clone()).unwrap(), Async::Ready(()));
+
+    notify.notify_one();
+    assert_eq!(c2.get(), 1);
+    assert_eq!(b.poll_future(c2.clone()).unwrap(), Async::Ready(()));
+    assert_empty(|| notify.notified());
+}
+
+#[test]
+fn notify_all() {
+    let notify = Notify::new();
+    let count = Count::new();
+    let mut waiters = (0..3).map(|_| task::spawn(notify.notified()))
+                            .collect::<Vec<_>>();
+    for w in waiters.iter_mut() {
+        assert!(w.poll_future(count.clone()).unwrap().is_not_ready());
+    }
+
+    // Futures which haven't been polled yet aren't waiting.
+    let mut late = task::spawn(notify.notified());
+    notify.notify_all();
+    assert_eq!(count.get(), 3);
+    for w in waiters.iter_mut() {
+        assert_eq!(w.poll_future(unpark_panic()).unwrap(), Async::Ready(()));
+    }
+    assert!(late.poll_future(unpark_noop()).unwrap().is_not_ready());
+}
+
+#[test]
+fn notify_dropped_waiter_passes_on() {
+    let notify = Notify::new();
+    let mut a = task::spawn(notify.notified());
+    let mut b = task::spawn(notify.notified());
+    assert!(a.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(b.poll_future(unpark_noop()).unwrap().is_not_ready());
+
+    // A notification of a waiter which goes away goes to the next one...
+    notify.notify_one();
+    drop(a);
+    assert_eq!(b.poll_future(unpark_panic()).unwrap(), Async::Ready(()));
+
+    // ... or is stored as a permit.
+    let mut c = task::spawn(notify.notified());
+    assert!(c.poll_future(unpark_noop()).unwrap().is_not_ready());
+    notify.notify_one();
+    drop(c);
+    assert_done(|| notify.notified(), Ok(()));
+
+    // Dropping a waiter which wasn't notified doesn't consume anything.
+    let mut d = task::spawn(notify.notified());
+    assert!(d.poll_future(unpark_noop()).unwrap().is_not_ready());
+    drop(d);
+    notify.notify_one();
+    assert_done(|| notify.notified(), Ok(()));
+}
+
+#[test]
+fn notify_threads() {
+    let notify = Notify::new();
+    let threads = (0..4).map(|_| {
+        let notify = notify.clone();
+        thread::spawn(move || notify.notified().wait().unwrap())
+    }).collect::<Vec<_>>();
+    for _ in 0..4 {
+        notify.notify_one();
+    }
+    for t in threads {
+        t.join().unwrap();
+    }
+}
+
+#[test]
+fn barrier() {
+    let barrier = Barrier::new(3);
+    let count = Count::new();
+    let mut a = task::spawn(barrier.wait());
+    let mut b = task::spawn(barrier.wait());
+    assert!(a.poll_future(count.clone()).unwrap().is_not_ready());
+    assert!(b.poll_future(count.clone()).unwrap().is_not_ready());
+    assert!(a.poll_future(count.clone()).unwrap().is_not_ready());
+
+    let leader = task::spawn(barrier.wait()).poll_future(unpark_panic());
+    assert!(leader.unwrap().map(|r| r.is_leader()) == Async::Ready(true));
+    assert_eq!(count.get(), 2);
+    for w in [a, b].iter_mut() {
+        match w.poll_future(unpark_panic()) {
+            Ok(Async::Ready(r)) => assert!(!r.is_leader()),
+            _ => panic!(),
+        }
+    }
+
+    // The barrier is reset for the next round, and tasks which give up
+    // waiting no longer count.
+    let mut c = task::spawn(barrier.wait());
+    assert!(c.poll_future(unpark_noop()).unwrap().is_not_ready());
+    drop(c);
+    let mut d = task::spawn(barrier.wait());
+    assert!(d.poll_future(unpark_noop()).unwrap().is_not_ready());
+    let mut e = task::spawn(barrier.wait());
+    assert!(e.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(d.poll_future(unpark_noop()).unwrap().is_not_ready());
+}
+
+#[test]
+fn barrier_of_one() {
+    for &n in [0, 1].iter() {
+        let barrier = Barrier::new(n);
+        for _ in 0..2 {
+            assert!(barrier.wait().wait().unwrap().is_leader());
+        }
+    }
+}
+
+#[test]
+fn barrier_threads() {
+    const N: usize = 8;
+
+    let barrier = Barrier::new(N);
+    let leaders = Arc::new(AtomicUsize::new(0));
+    let threads = (0..N).map(|_| {
+        let barrier = barrier.clone();
+        let leaders = leaders.clone();
+        thread::spawn(move || {
+            for _ in 0..10 {
+                if barrier.wait().wait().unwrap().is_leader() {
+                    leaders.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        })
+    }).collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap();
+    }
+    assert_eq!(leaders.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+fn once_cell() {
+    let cell = OnceCell::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_init(|| finished::<u32, ()>(1)).wait(), Ok(&1));
+    assert_eq!(cell.get_or_init(|| -> Result<u32, ()> { panic!() }).wait(),
+               Ok(&1));
+    assert_eq!(cell.get(), Some(&1));
+}
+
+#[test]
+fn once_cell_waiters() {
+    let cell = OnceCell::new();
+    let (c, p) = oneshot::<u32>();
+    let count = Count::new();
+    let mut a = task::spawn(cell.get_or_init(|| p));
+    let mut b = task::spawn(cell.get_or_init(|| -> Result<u32, Canceled> {
+        panic!()
+    }));
+    assert!(a.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(b.poll_future(count.clone()).unwrap().is_not_ready());
+    assert!(b.poll_future(count.clone()).unwrap().is_not_ready());
+    assert_eq!(cell.get(), None);
+
+    c.complete(2);
+    assert_eq!(a.poll_future(unpark_panic()).unwrap(), Async::Ready(&2));
+    assert_eq!(count.get(), 1);
+    assert_eq!(b.poll_future(unpark_panic()).unwrap(), Async::Ready(&2));
+}
+
+#[test]
+fn once_cell_retries() {
+    let cell = OnceCell::new();
+    let (c, p) = oneshot::<Result<u32, u32>>();
+    let count = Count::new();
+    let mut a = task::spawn(cell.get_or_init(|| p.then(|r| r.unwrap())));
+    let mut b = task::spawn(cell.get_or_init(|| finished::<u32, u32>(3)));
+    assert!(a.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(b.poll_future(count.clone()).unwrap().is_not_ready());
+
+    // The failed initializer's error goes to its own task, and the waiting
+    // task takes over.
+    c.complete(Err(1));
+    assert_eq!(a.poll_future(unpark_panic()), Err(1));
+    assert_eq!(count.get(), 1);
+    assert_eq!(b.poll_future(unpark_panic()).unwrap(), Async::Ready(&3));
+
+    // The same happens if the initializing future is dropped.
+    let cell = OnceCell::new();
+    let (_c, p) = oneshot::<u32>();
+    let count = Count::new();
+    let mut a = task::spawn(cell.get_or_init(|| p));
+    let mut b = task::spawn(cell.get_or_init(|| failed(4)));
+    let mut c = task::spawn(cell.get_or_init(|| finished::<u32, u32>(5)));
+    assert!(a.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(b.poll_future(count.clone()).unwrap().is_not_ready());
+    assert!(c.poll_future(count.clone()).unwrap().is_not_ready());
+    drop(a);
+    assert_eq!(count.get(), 2);
+    assert!(b.poll_future(unpark_panic()).is_err());
+    assert_eq!(c.poll_future(unpark_panic()).unwrap(), Async::Ready(&5));
+}
+
+#[test]
+fn once_cell_panicking_init() {
+    let cell = OnceCell::new();
+    let count = Count::new();
+    let mut b = task::spawn(cell.get_or_init(|| finished::<u32, ()>(6)));
+
+    // A panic in the initializer releases the cell, and the waiting task
+    // takes over.
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut a = task::spawn(cell.get_or_init(|| -> Result<u32, ()> {
+            assert!(b.poll_future(count.clone()).unwrap().is_not_ready());
+            panic!()
+        }));
+        a.poll_future(unpark_noop())
+    }));
+    assert!(res.is_err());
+    assert_eq!(count.get(), 1);
+    assert_eq!(cell.get(), None);
+    assert_eq!(b.poll_future(unpark_panic()).unwrap(), Async::Ready(&6));
+    assert_eq!(cell.get(), Some(&6));
+}
+
+#[test]
+fn once_cell_threads() {
+    let cell = Arc::new(OnceCell::new());
+    let inits = Arc::new(AtomicUsize::new(0));
+    let threads = (0..8).map(|i| {
+        let cell = cell.clone();
+        let inits = inits.clone();
+        thread::spawn(move || {
+            let value = cell.get_or_init(|| {
+                inits.fetch_add(1, Ordering::SeqCst);
+                thread::yield_now();
+                finished::<usize, ()>(i)
+            }).wait().unwrap();
+            *value
+        })
+    }).collect::<Vec<_>>();
+    let values = threads.into_iter()
+                        .map(|t| t.join().unwrap())
+                        .collect::<Vec<_>>();
+    assert_eq!(inits.load(Ordering::SeqCst), 1);
+    assert!(values.iter().all(|&v| Some(&v) == cell.get()));
+}
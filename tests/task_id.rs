@@ -0,0 +1,76 @@
+extern crate futures;
+
+use std::collections::HashSet;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use futures::finished;
+use futures::task::{self, TaskRc};
+
+// Ids are allocated from a global pool, so everything spawning tasks lives in
+// one test.
+#[test]
+fn ids_are_recycled() {
+    let a = task::spawn(finished::<(), ()>(()));
+    let b = task::spawn(finished::<(), ()>(()));
+    let (a_id, b_id) = (a.id(), b.id());
+    assert!(a_id != b_id);
+    assert!(a_id.index() != b_id.index());
+
+    // A new task reuses the index of a dropped one, but still gets a distinct
+    // id.
+    drop(a);
+    let c = task::spawn(finished::<(), ()>(()));
+    assert_eq!(c.id().index(), a_id.index());
+    assert!(c.id() != a_id);
+
+    let ids = [a_id, b_id, c.id()].iter().cloned().collect::<HashSet<_>>();
+    assert_eq!(ids.len(), 3);
+    drop((b, c));
+
+    // Spawning many short-lived tasks doesn't grow the indices.
+    let max = (0..1000).map(|_| task::spawn(()).id().index()).max().unwrap();
+    assert!(max < 10);
+
+    // The id seen from within a task matches the id of its `Spawn`.
+    let mut t = task::spawn(futures::lazy(|| {
+        Ok::<_, ()>(task::park().id())
+    }));
+    let id = t.id();
+    assert_eq!(t.wait_future(), Ok(id));
+    drop(t);
+
+    // Data created by a task isn't accessible to a later task reusing its
+    // index.
+    let mut t = task::spawn(futures::lazy(|| Ok::<_, ()>(TaskRc::new(1))));
+    let rc = t.wait_future().unwrap();
+    let old = t.id();
+    drop(t);
+    let mut t = task::spawn(futures::lazy(move || {
+        Ok::<_, ()>(rc.with(|a| *a))
+    }));
+    assert_eq!(t.id().index(), old.index());
+    let res = thread::spawn(move || t.wait_future()).join();
+    assert!(res.is_err());
+
+    // Tasks spawned concurrently on many threads get distinct ids.
+    let barrier = Arc::new(Barrier::new(8));
+    let threads = (0..8).map(|_| {
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            for _ in 0..100 {
+                drop(task::spawn(()));
+            }
+            let tasks = (0..100).map(|_| task::spawn(())).collect::<Vec<_>>();
+            let ids = tasks.iter().map(|t| t.id()).collect::<Vec<_>>();
+            // Keep the tasks alive until every thread has spawned its own.
+            barrier.wait();
+            ids
+        })
+    }).collect::<Vec<_>>();
+    let ids = threads.into_iter()
+                     .flat_map(|t| t.join().unwrap())
+                     .collect::<Vec<_>>();
+    let indices = ids.iter().map(|id| id.index()).collect::<HashSet<_>>();
+    assert_eq!(indices.len(), 800);
+}
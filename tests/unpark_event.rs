@@ -0,0 +1,69 @@
+extern crate futures;
+
+use std::sync::{Arc, Mutex};
+
+use futures::Async;
+use futures::task::{self, EventSet, UnparkEvent, Task};
+
+mod support;
+use support::*;
+
+struct Set(Mutex<Vec<usize>>);
+
+impl EventSet for Set {
+    fn insert(&self, id: usize) {
+        self.0.lock().unwrap().push(id);
+    }
+}
+
+// Parks the current task from within `depth` nested unpark events.
+fn park_nested(set: &Arc<Set>, depth: usize) -> Task {
+    if depth == 0 {
+        return task::park()
+    }
+    let event = UnparkEvent::new(set.clone(), depth);
+    task::with_unpark_event(event, || park_nested(set, depth - 1))
+}
+
+#[test]
+fn nested_events() {
+    for depth in 0..6 {
+        let set = Arc::new(Set(Mutex::new(Vec::new())));
+        let set2 = set.clone();
+        let mut t = task::spawn(futures::lazy(move || {
+            Ok::<_, ()>(park_nested(&set2, depth))
+        }));
+        let handle = match t.poll_future(unpark_noop()) {
+            Ok(Async::Ready(handle)) => handle,
+            _ => panic!(),
+        };
+        assert!(set.0.lock().unwrap().is_empty());
+
+        // Clones of a handle trigger the same events.
+        let handles = vec![handle.clone(), handle];
+        for (i, handle) in handles.into_iter().enumerate() {
+            handle.unpark();
+            let mut events = set.0.lock().unwrap().split_off(0);
+            events.sort();
+            assert_eq!(events, (1..depth + 1).collect::<Vec<_>>(),
+                       "depth {} handle {}", depth, i);
+        }
+    }
+}
+
+#[test]
+fn events_are_scoped() {
+    let set = Arc::new(Set(Mutex::new(Vec::new())));
+    let set2 = set.clone();
+    let mut t = task::spawn(futures::lazy(move || {
+        let inner = task::with_unpark_event(UnparkEvent::new(set2.clone(), 1), || {
+            task::park()
+        });
+        Ok::<_, ()>((inner, task::park()))
+    }));
+    let (inner, outer) = t.wait_future().unwrap();
+    outer.unpark();
+    assert!(set.0.lock().unwrap().is_empty());
+    inner.unpark();
+    assert_eq!(*set.0.lock().unwrap(), vec![1]);
+}